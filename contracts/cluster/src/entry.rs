@@ -6,7 +6,7 @@ use spore_types::generated::action;
 // Import from `core` instead of from `std` since we are in no-std mode
 use core::result::Result;
 
-use ckb_std::ckb_constants::Source::{CellDep, GroupInput, GroupOutput, Output};
+use ckb_std::ckb_constants::Source::{CellDep, GroupInput, GroupOutput, Input, Output};
 use ckb_std::ckb_types::packed::Script;
 use ckb_std::{
     ckb_constants::Source,
@@ -18,9 +18,11 @@ use ckb_std::{
 use ckb_std::high_level::{load_script, QueryIter};
 use spore_errors::error::Error;
 use spore_types::generated::spore::ClusterDataV2 as ClusterData;
+use spore_utils::merkle::ALLOWLIST_ROOT_LEN;
 use spore_utils::{
-    check_spore_address, extract_spore_action, find_position_by_type, find_position_by_type_args,
-    load_self_id, verify_type_id,
+    check_spore_address, compatible_load_cluster_data, exec_cluster_lifecycle_mutant,
+    extract_spore_action, find_mutant_position, find_position_by_type, find_position_by_type_args,
+    load_self_id, verify_type_id, MUTANT_OP_CLUSTER_TRANSFER,
 };
 
 use crate::hash::SPORE_EXTENSION_LUA;
@@ -50,6 +52,22 @@ fn process_creation(index: usize) -> Result<(), Error> {
             .ok_or(Error::MutantNotInDeps)?;
     }
 
+    let raw_cluster_data = load_cell_data(index, Output)?;
+    let compatible_cluster_data = compatible_load_cluster_data(&raw_cluster_data)?;
+
+    // Verify if allowlist root is set
+    if let Some(allowlist_root) = compatible_cluster_data.allowlist_root().to_opt() {
+        if allowlist_root.raw_data().len() != ALLOWLIST_ROOT_LEN {
+            return Err(Error::InvalidAllowlistRoot);
+        }
+    }
+
+    // Verify if lifecycle mutant is set
+    if let Some(mutant_id) = compatible_cluster_data.lifecycle_mutant_id().to_opt() {
+        find_mutant_position(&mutant_id.raw_data(), &SPORE_EXTENSION_LUA)
+            .ok_or(Error::MutantNotInDeps)?;
+    }
+
     // check co-build action @lyk
     let action::SporeActionUnion::MintCluster(mint) = extract_spore_action()?.to_enum() else {
         return Err(Error::SporeActionMismatch);
@@ -84,6 +102,19 @@ fn process_transfer() -> Result<(), Error> {
     check_spore_address(GroupInput, transfer.from())?;
     check_spore_address(GroupOutput, transfer.to())?;
 
+    // run lifecycle mutant at last, since exec never returns
+    let raw_cluster_data = load_cell_data(0, GroupInput)?;
+    let cluster_data = compatible_load_cluster_data(&raw_cluster_data)?;
+    let script = load_script()?;
+    let input_index = find_position_by_type(&script, Input).unwrap_or_default();
+    let output_index = find_position_by_type(&script, Output).unwrap_or_default();
+    exec_cluster_lifecycle_mutant(
+        &cluster_data,
+        &SPORE_EXTENSION_LUA,
+        MUTANT_OP_CLUSTER_TRANSFER,
+        &[input_index, output_index],
+    )?;
+
     Ok(())
 }
 
@@ -3,19 +3,33 @@ use std::{env, fs};
 
 use spore_build_tools::{concat_code_hashes, load_frozen_toml};
 
-pub fn main() {
-    let compile_mode = env::var("PROFILE").unwrap();
-    let cluster_proxy_path = env::current_dir()
+fn load_code_hash(binary_name: &str, compile_mode: &str) -> [u8; 32] {
+    let binary_path = env::current_dir()
         .unwrap()
         .join("../../build")
         .join(compile_mode)
-        .join("cluster_proxy");
-    let cluster_proxy = std::fs::read(cluster_proxy_path).expect("load cluster_proxy");
-    let code_hash = blake2b_256(cluster_proxy);
+        .join(binary_name);
+    let binary = std::fs::read(binary_path).unwrap_or_else(|_| panic!("load {binary_name}"));
+    blake2b_256(binary)
+}
+
+pub fn main() {
+    let compile_mode = env::var("PROFILE").unwrap();
+    let cluster_code_hash = load_code_hash("cluster", &compile_mode);
+    let cluster_proxy_code_hash = load_code_hash("cluster_proxy", &compile_mode);
+    let mutant_code_hash = load_code_hash("spore_extension_lua", &compile_mode);
 
     let frozen = load_frozen_toml();
-    let code_hashes = [frozen.cluster_proxy_code_hashes(), vec![code_hash]].concat();
+    let cluster_code_hashes = [frozen.cluster_code_hashes(), vec![cluster_code_hash]].concat();
+    let cluster_proxy_code_hashes = [
+        frozen.cluster_proxy_code_hashes(),
+        vec![cluster_proxy_code_hash],
+    ]
+    .concat();
+    let mutant_code_hashes = [frozen.mutant_code_hashes(), vec![mutant_code_hash]].concat();
 
-    let file = concat_code_hashes("CLUSTER_PROXY_CODE_HASHES", &code_hashes);
-    fs::write("./src/hash.rs", file).unwrap();
+    let mut content = concat_code_hashes("CLUSTER_CODE_HASHES", &cluster_code_hashes);
+    content += concat_code_hashes("CLUSTER_PROXY_CODE_HASHES", &cluster_proxy_code_hashes).as_str();
+    content += concat_code_hashes("SPORE_EXTENSION_LUA", &mutant_code_hashes).as_str();
+    fs::write("./src/hash.rs", content).unwrap();
 }
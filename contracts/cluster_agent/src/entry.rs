@@ -15,17 +15,23 @@ use ckb_std::{ckb_types::prelude::*, debug, high_level::load_script};
 use spore_errors::error::Error;
 use spore_types::generated::action;
 use spore_utils::{
-    calc_capacity_sum, find_position_by_type, find_position_by_type_hash, load_self_id,
+    calc_capacity_sum, find_position_by_type, find_position_by_type_args,
+    find_position_by_type_hash, load_self_id,
 };
 use spore_utils::{
-    check_spore_address, extract_spore_action, CLUSTER_PROXY_ID_LEN,
-    CLUSTER_PROXY_ID_WITH_PAYMENT_LEN,
+    check_spore_address, compatible_load_cluster_data, exec_cluster_lifecycle_mutant,
+    extract_spore_action, CLUSTER_PROXY_ID_LEN, CLUSTER_PROXY_ID_WITH_PAYMENT_LEN,
+    MUTANT_OP_AGENT_CREATION,
 };
 
 fn is_valid_cluster_proxy_cell(script_hash: &[u8; 32]) -> bool {
     crate::hash::CLUSTER_PROXY_CODE_HASHES.contains(script_hash)
 }
 
+fn is_valid_cluster_cell(script_hash: &[u8; 32]) -> bool {
+    crate::hash::CLUSTER_CODE_HASHES.contains(script_hash)
+}
+
 fn has_conflict_agent(source: Source, cell_data: &[u8]) -> bool {
     let script = load_script().unwrap_or_default();
     let self_code_hash = script.code_hash();
@@ -44,7 +50,7 @@ fn has_conflict_agent(source: Source, cell_data: &[u8]) -> bool {
     agents_count > 1
 }
 
-fn process_creation(_index: usize) -> Result<(), Error> {
+fn process_creation(index: usize) -> Result<(), Error> {
     let proxy_type_hash = load_cell_data(0, GroupOutput)?;
     // check cluster proxy in Deps
     let proxy_index = find_position_by_type_hash(proxy_type_hash.as_slice(), CellDep)
@@ -61,6 +67,10 @@ fn process_creation(_index: usize) -> Result<(), Error> {
         return Err(Error::InvalidAgentArgs);
     }
 
+    // cluster in Deps is optional, it's only required to run the lifecycle mutant of cluster
+    let cluster_index =
+        find_position_by_type_args(&cluster_id, CellDep, Some(is_valid_cluster_cell));
+
     // Condition 1: Check if cluster proxy exist in Inputs & Outputs
     let proxy_cell_in_input =
         find_position_by_type_hash(proxy_type_hash.as_slice(), Input).is_some();
@@ -108,6 +118,18 @@ fn process_creation(_index: usize) -> Result<(), Error> {
     }
     check_spore_address(GroupOutput, mint.to())?;
 
+    // run lifecycle mutant at last, since exec never returns
+    if let Some(cluster_index) = cluster_index {
+        let raw_cluster_data = load_cell_data(cluster_index, CellDep)?;
+        let cluster_data = compatible_load_cluster_data(&raw_cluster_data)?;
+        exec_cluster_lifecycle_mutant(
+            &cluster_data,
+            &crate::hash::SPORE_EXTENSION_LUA,
+            MUTANT_OP_AGENT_CREATION,
+            &[cluster_index, proxy_index, index],
+        )?;
+    }
+
     Ok(())
 }
 
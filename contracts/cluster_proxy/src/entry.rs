@@ -12,9 +12,10 @@ use ckb_std::high_level::{
 use spore_errors::error::Error;
 use spore_types::generated::action;
 use spore_utils::{
-    check_spore_address, extract_spore_action, find_position_by_lock_hash, find_position_by_type,
+    check_spore_address, compatible_load_cluster_data, exec_cluster_lifecycle_mutant,
+    extract_spore_action, find_position_by_lock_hash, find_position_by_type,
     find_position_by_type_args, load_self_id, verify_type_id, CLUSTER_PROXY_ID_LEN,
-    CLUSTER_PROXY_ID_WITH_PAYMENT_LEN,
+    CLUSTER_PROXY_ID_WITH_PAYMENT_LEN, MUTANT_OP_PROXY_CREATION,
 };
 
 fn is_valid_cluster_cell(script_hash: &[u8; 32]) -> bool {
@@ -63,6 +64,16 @@ fn process_creation(index: usize) -> Result<(), Error> {
     }
     check_spore_address(GroupOutput, create.to())?;
 
+    // run lifecycle mutant at last, since exec never returns
+    let raw_cluster_data = load_cell_data(cell_dep_index, CellDep)?;
+    let cluster_data = compatible_load_cluster_data(&raw_cluster_data)?;
+    exec_cluster_lifecycle_mutant(
+        &cluster_data,
+        &crate::hash::SPORE_EXTENSION_LUA,
+        MUTANT_OP_PROXY_CREATION,
+        &[cell_dep_index, index],
+    )?;
+
     Ok(())
 }
 
@@ -18,6 +18,7 @@ pub fn main() {
     let cluster_code_hash = load_code_hash("cluster", &compile_mode);
     let cluster_agent_code_hash = load_code_hash("cluster_agent", &compile_mode);
     let mutant_code_hash = load_code_hash("spore_extension_lua", &compile_mode);
+    let mutant_rules_code_hash = load_code_hash("spore_extension_rules", &compile_mode);
 
     let frozen = load_frozen_toml();
     let cluster_code_hashes = [frozen.cluster_code_hashes(), vec![cluster_code_hash]].concat();
@@ -26,10 +27,21 @@ pub fn main() {
         vec![cluster_agent_code_hash],
     ]
     .concat();
-    let mutant_code_hashes = [frozen.mutant_code_hashes(), vec![mutant_code_hash]].concat();
+    // both kinds of extension are registered as mutants, Lua ones first
+    let mutant_code_hashes = [
+        frozen.mutant_code_hashes(),
+        vec![mutant_code_hash],
+        frozen.mutant_rules_code_hashes(),
+        vec![mutant_rules_code_hash],
+    ]
+    .concat();
+
+    // the current spore code hash can't be known while building itself, it's loaded at runtime
+    let spore_code_hashes = frozen.spore_code_hashes();
 
     let mut content = concat_code_hashes("CLUSTER_CODE_HASHES", &cluster_code_hashes);
     content += concat_code_hashes("CLUSTER_AGENT_CODE_HASHES", &cluster_agent_code_hashes).as_str();
     content += concat_code_hashes("MUTANT_CODE_HASHES", &mutant_code_hashes).as_str();
+    content += concat_code_hashes("SPORE_CODE_HASHES", &spore_code_hashes).as_str();
     fs::write("./src/hash.rs", content).unwrap();
 }
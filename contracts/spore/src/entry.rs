@@ -1,13 +1,12 @@
 use alloc::collections::BTreeMap;
-use alloc::{format, vec, vec::Vec};
+use alloc::{ffi::CString, format, string::String, vec, vec::Vec};
 use ckb_std::ckb_types::util::hash::blake2b_256;
-use core::ffi::CStr;
 use core::result::Result;
 
 use ckb_std::ckb_constants::Source::{CellDep, GroupInput, GroupOutput, Input, Output};
 use ckb_std::ckb_types::core::ScriptHashType;
 use ckb_std::ckb_types::packed::Script;
-use ckb_std::high_level::{load_cell_lock_hash, load_script};
+use ckb_std::high_level::{load_cell_lock_hash, load_input_since, load_script};
 use ckb_std::{
     ckb_constants::Source,
     ckb_types::prelude::*,
@@ -17,14 +16,21 @@ use ckb_std::{
 
 use spore_errors::error::Error;
 use spore_types::generated::action;
-use spore_types::generated::spore::SporeData;
+use spore_types::generated::spore::{PublicMint, SporeData};
+use spore_utils::merkle::verify_allowlist_proof;
 use spore_utils::{
     calc_capacity_sum, check_spore_address, compatible_load_cluster_data, extract_spore_action,
-    find_position_by_lock_hash, find_position_by_type, find_position_by_type_args, load_self_id,
-    verify_type_id, MIME, MUTANT_ID_LEN, MUTANT_ID_WITH_PAYMENT_LEN,
+    extract_spore_refs, find_position_by_lock_hash, find_position_by_type,
+    find_position_by_type_args, is_spore_ref_content, load_self_id, mutant_payment,
+    mutant_payment_recipient, parse_absolute_epoch_since, parse_multipart, split_mutant_data,
+    validate_content, verify_type_id, MAX_MULTIPART_PARTS, MIME, MUTANT_ARGV_NEXT_EXTENSION,
+    MUTANT_ID_LEN, MUTANT_ID_WITH_PAYMENT_LEN, MUTANT_OP_SPORE_BURN, MUTANT_OP_SPORE_MINT,
+    MUTANT_OP_SPORE_TRANSFER,
 };
 
-use crate::hash::{CLUSTER_AGENT_CODE_HASHES, CLUSTER_CODE_HASHES};
+use crate::hash::{
+    CLUSTER_AGENT_CODE_HASHES, CLUSTER_CODE_HASHES, MUTANT_CODE_HASHES, SPORE_CODE_HASHES,
+};
 
 enum Operation {
     Mint,
@@ -32,6 +38,16 @@ enum Operation {
     Burn,
 }
 
+impl Operation {
+    fn opcode(&self) -> usize {
+        match self {
+            Operation::Mint => MUTANT_OP_SPORE_MINT,
+            Operation::Transfer => MUTANT_OP_SPORE_TRANSFER,
+            Operation::Burn => MUTANT_OP_SPORE_BURN,
+        }
+    }
+}
+
 fn check_cluster_code_hash(code_hash: &[u8; 32]) -> bool {
     CLUSTER_CODE_HASHES.contains(code_hash)
 }
@@ -40,6 +56,14 @@ fn check_agent_code_hash(code_hash: &[u8; 32]) -> bool {
     CLUSTER_AGENT_CODE_HASHES.contains(code_hash)
 }
 
+// both frozen versions and the running one of spore contract
+fn check_spore_code_hash(code_hash: &[u8; 32]) -> bool {
+    SPORE_CODE_HASHES.contains(code_hash)
+        || load_script()
+            .map(|script| script.code_hash().as_slice() == code_hash)
+            .unwrap_or(false)
+}
+
 fn load_spore_data(index: usize, source: Source) -> Result<SporeData, Error> {
     let raw_data = load_cell_data(index, source)?;
     let spore_data = SporeData::from_compatible_slice(raw_data.as_slice())
@@ -54,8 +78,8 @@ fn process_creation(index: usize) -> Result<(), Error> {
         return Err(Error::EmptyContent);
     }
 
-    let content = spore_data.content();
-    let content_arr = content.as_slice();
+    let content = spore_data.content().raw_data();
+    let content_arr = content.as_ref();
 
     if spore_data.content_type().is_empty() {
         return Err(Error::InvalidContentType);
@@ -69,34 +93,38 @@ fn process_creation(index: usize) -> Result<(), Error> {
     // content_type validation
     let content_type = spore_data.content_type().raw_data();
     let mime = MIME::parse(&content_type)?;
-    verify_extension(&mime, Operation::Mint, vec![index as u8])?;
 
     // Spore supports [MIME-multipart](https://datatracker.ietf.org/doc/html/rfc1521#section-7.2).
     //
     // The Multipart Content-Type is used to represent a document that is comprised of multiple
     // parts, each of which may have its own individual MIME type
-    if content_type[mime.main_type.clone()] == "multipart".as_bytes()[..] {
+    if content_type[mime.main_type.clone()].eq_ignore_ascii_case(b"multipart") {
         // Check if boundary param exists
         // The Content-Type field for multipart entities requires one parameter, "boundary", which
         // is used to specify the encapsulation boundary. See Appendix C of rfc1521 for a complex
         // multipart example.
         debug!("check mime multipart specification");
-        let boundary_range = mime
-            .get_param(&content_type, "boundary")?
+        // parts can't be checked in encoded content, so each part should be encoded instead
+        if mime.encoding.is_some() {
+            return Err(Error::UnsupportedContentEncoding);
+        }
+        let boundary = mime
+            .get_param_value(&content_type, "boundary")?
             .ok_or(Error::InvalidContentType)?;
-        kmp::kmp_find(
-            format!(
-                "--{}",
-                alloc::str::from_utf8(&content_type[boundary_range])
-                    .or(Err(Error::BoundaryEncoding))?
-            )
-            .as_bytes(),
-            content_arr,
-        )
-        .ok_or(Error::InvalidMultipartContent)?;
+        // Check parts are enclosed by delimiters and each content type of them is valid
+        parse_multipart(content_arr, &boundary, Some(MAX_MULTIPART_PARTS))?;
+    } else {
+        // Validate content if its content type asks for, e.g. `charset=utf-8` or `application/json`
+        let decoded = validate_content(&content_type, &mime, content_arr)?;
+        if mime.strict && is_spore_ref_content(&content_type, &mime) {
+            debug!("check referred spores in strict mode");
+            // encoded content is always decoded in strict mode, so it's reused here
+            check_spore_refs(decoded.as_deref().unwrap_or(content_arr))?;
+        }
     }
 
     // check in Cluster mode
+    let mut allowlist_root = None;
     if let Some(cluster_id) = spore_data.cluster_id().to_opt() {
         debug!("check in cluster mode");
         // check if cluster cell is in deps
@@ -108,6 +136,7 @@ fn process_creation(index: usize) -> Result<(), Error> {
         // the cluster contract guarantees the cluster data will always be correct once created
         let raw_cluster_data = load_cell_data(cell_dep_index, CellDep)?;
         let cluster_data = compatible_load_cluster_data(&raw_cluster_data)?;
+        allowlist_root = cluster_data.allowlist_root().to_opt();
 
         // check in Mutant mode
         if let Some(mutant_id) = cluster_data.mutant_id().to_opt() {
@@ -134,42 +163,154 @@ fn process_creation(index: usize) -> Result<(), Error> {
         let agent_cell_in_output =
             find_position_by_type_args(&cluster_id, Output, Some(check_agent_code_hash)).is_some();
 
-        if (!cluster_cell_in_input || !cluster_cell_in_output)
-            && (!agent_cell_in_input || !agent_cell_in_output)
-        {
+        let cluster_lock_hash = load_cell_lock_hash(cell_dep_index, CellDep)?;
+        let mut ownership_passed = (cluster_cell_in_input && cluster_cell_in_output)
+            || (agent_cell_in_input && agent_cell_in_output);
+        if !ownership_passed {
             // Condition 3: Use cluster agent in Lock Proxy mode
-            if let Some(agent_index) =
+            ownership_passed = if let Some(agent_index) =
                 find_position_by_type_args(&cluster_id, CellDep, Some(check_agent_code_hash))
             {
                 debug!("check in agent mode");
                 let agent_lock_hash = load_cell_lock_hash(agent_index, CellDep)?;
-                find_position_by_lock_hash(&agent_lock_hash, Output)
-                    .ok_or(Error::ClusterOwnershipVerifyFailed)?;
-                find_position_by_lock_hash(&agent_lock_hash, Input)
-                    .ok_or(Error::ClusterOwnershipVerifyFailed)?;
+                is_lock_proxy_passed(&agent_lock_hash)
             } else {
                 debug!("check in lock proxy mode");
                 // Condition 4: Check if Lock Proxy exist in Inputs & Outputs
-                let cluster_lock_hash = load_cell_lock_hash(cell_dep_index, CellDep)?;
-                find_position_by_lock_hash(&cluster_lock_hash, Output)
-                    .ok_or(Error::ClusterOwnershipVerifyFailed)?;
-                find_position_by_lock_hash(&cluster_lock_hash, Input)
-                    .ok_or(Error::ClusterOwnershipVerifyFailed)?;
-            }
+                is_lock_proxy_passed(&cluster_lock_hash)
+            };
+        }
+        if !ownership_passed {
+            // Condition 5: Check if public minting is opened by cluster, which works no matter
+            // how the cluster would be unlocked otherwise
+            let public_mint = cluster_data
+                .public_mint()
+                .to_opt()
+                .ok_or(Error::ClusterOwnershipVerifyFailed)?;
+            debug!("check in public mint mode");
+            check_public_mint(&public_mint, &cluster_id, &cluster_lock_hash)?;
         }
     }
 
     // check co-build action @lyk
-    let action::SporeActionUnion::MintSpore(mint) = extract_spore_action()?.to_enum() else {
-        return Err(Error::SporeActionMismatch);
+    let (action_spore_id, data_hash, to, allowlist_proof) = match extract_spore_action()?.to_enum()
+    {
+        action::SporeActionUnion::MintSpore(mint) => {
+            (mint.spore_id(), mint.data_hash(), mint.to(), None)
+        }
+        action::SporeActionUnion::MintSporeWithProof(mint) => (
+            mint.spore_id(),
+            mint.data_hash(),
+            mint.to(),
+            Some(mint.allowlist_proof()),
+        ),
+        _ => return Err(Error::SporeActionMismatch),
     };
-    if mint.spore_id().as_slice() != spore_id
-        || mint.data_hash().as_slice() != blake2b_256(spore_data.as_slice())
+    if action_spore_id.as_slice() != spore_id
+        || data_hash.as_slice() != blake2b_256(spore_data.as_slice())
     {
         return Err(Error::SporeActionFieldMismatch);
     }
-    check_spore_address(GroupOutput, mint.to())?;
 
+    // check in Allowlist mode
+    if let Some(allowlist_root) = allowlist_root {
+        let allowlist_proof = allowlist_proof.ok_or(Error::AllowlistProofMissing)?;
+        verify_allowlist(&allowlist_root.raw_data(), &to, &allowlist_proof)?;
+    }
+    check_spore_address(GroupOutput, to)?;
+
+    // mutants are executed in the end, because exec never returns once succeeded
+    verify_extension(&spore_data, &mime, Operation::Mint, vec![index])
+}
+
+fn is_lock_proxy_passed(lock_hash: &[u8; 32]) -> bool {
+    find_position_by_lock_hash(lock_hash, Output).is_some()
+        && find_position_by_lock_hash(lock_hash, Input).is_some()
+}
+
+fn check_public_mint(
+    public_mint: &PublicMint,
+    cluster_id: &[u8],
+    cluster_lock_hash: &[u8; 32],
+) -> Result<(), Error> {
+    // the minting epoch is proved by absolute epoch since of any input, which only bounds the
+    // epoch from below, so public minting can be opened but never closed by epoch
+    let start_epoch = public_mint.start_epoch().unpack();
+    let started = QueryIter::new(load_input_since, Input)
+        .any(|since| parse_absolute_epoch_since(since).map_or(false, |epoch| epoch >= start_epoch));
+    if !started {
+        return Err(Error::PublicMintWindowMismatch);
+    }
+
+    // every spore minted into the cluster in this transaction should be paid
+    let price = public_mint.price().unpack();
+    if price > 0 {
+        let minted_count = count_minted_spores_in_cluster(cluster_id)?;
+        let input_capacity = calc_capacity_sum(cluster_lock_hash, Input);
+        let output_capacity = calc_capacity_sum(cluster_lock_hash, Output);
+        let expected_capacity = price
+            .checked_mul(minted_count)
+            .and_then(|payment| payment.checked_add(input_capacity))
+            .ok_or(Error::PublicMintPaymentNotEnough)?;
+        if expected_capacity > output_capacity {
+            return Err(Error::PublicMintPaymentNotEnough);
+        }
+    }
+    Ok(())
+}
+
+fn check_spore_refs(content: &[u8]) -> Result<(), Error> {
+    // referred spores must be in cell deps and typed by any version of spore contract
+    for spore_id in extract_spore_refs(content)? {
+        find_position_by_type_args(&spore_id, CellDep, Some(check_spore_code_hash))
+            .ok_or(Error::SporeRefNotInDep)?;
+    }
+    Ok(())
+}
+
+// only spores of this contract are counted, since older spore contracts don't support public minting
+// and have to verify the ownership of cluster by themselves
+fn count_minted_spores_in_cluster(cluster_id: &[u8]) -> Result<u64, Error> {
+    let spore_code_hash = load_script()?.code_hash();
+    let mut minted_count = 0;
+    for (index, type_) in QueryIter::new(load_cell_type, Output).enumerate() {
+        let Some(type_) = type_ else {
+            continue;
+        };
+        if type_.code_hash().as_slice() != spore_code_hash.as_slice()
+            || find_position_by_type(&type_, Input).is_some()
+        {
+            continue;
+        }
+        let spore_data = load_spore_data(index, Output)?;
+        if let Some(id) = spore_data.cluster_id().to_opt() {
+            if id.raw_data().as_ref() == cluster_id {
+                minted_count += 1;
+            }
+        }
+    }
+    Ok(minted_count)
+}
+
+fn verify_allowlist(
+    allowlist_root: &[u8],
+    to: &action::Address,
+    allowlist_proof: &action::Byte32Vec,
+) -> Result<(), Error> {
+    debug!("check in allowlist mode");
+    let allowlist_root: [u8; 32] = allowlist_root
+        .try_into()
+        .map_err(|_| Error::InvalidClusterData)?;
+    let action::AddressUnion::Script(to) = to.to_enum();
+    let to_lock_hash = blake2b_256(to.as_slice());
+    let proof: Vec<[u8; 32]> = allowlist_proof
+        .clone()
+        .into_iter()
+        .map(|hash| hash.as_slice().try_into().unwrap_or_default())
+        .collect();
+    if !verify_allowlist_proof(&allowlist_root, &to_lock_hash, &proof) {
+        return Err(Error::AllowlistVerifyFailed);
+    }
     Ok(())
 }
 
@@ -177,18 +318,13 @@ fn process_destruction() -> Result<(), Error> {
     let spore_data = load_spore_data(0, GroupInput)?;
     let content_type = spore_data.content_type().raw_data();
 
-    let mime = MIME::parse(&content_type)?;
+    // the strict grammar only applies to mint, spores minted under the legacy one are still burnable
+    let mime = MIME::parse_lenient(&content_type)?;
     if mime.immortal {
         // true destroy a immortal nft
         return Err(Error::DestroyImmortalNFT);
     }
 
-    if !mime.mutants.is_empty() {
-        let spore_type = load_script()?;
-        let index = find_position_by_type(&spore_type, Input).ok_or(Error::IndexOutOfBound)?;
-        verify_extension(&mime, Operation::Burn, vec![index as u8])?;
-    }
-
     // check co-build action @lyk
     let action::SporeActionUnion::BurnSpore(burn) = extract_spore_action()?.to_enum() else {
         return Err(Error::SporeActionMismatch);
@@ -198,6 +334,12 @@ fn process_destruction() -> Result<(), Error> {
     }
     check_spore_address(GroupInput, burn.from())?;
 
+    if !mime.mutants.is_empty() {
+        let spore_type = load_script()?;
+        let index = find_position_by_type(&spore_type, Input).ok_or(Error::IndexOutOfBound)?;
+        verify_extension(&spore_data, &mime, Operation::Burn, vec![index])?;
+    }
+
     Ok(())
 }
 
@@ -211,7 +353,20 @@ fn process_transfer() -> Result<(), Error> {
     }
 
     let content_type = input_data.content_type().raw_data();
-    let mime = MIME::parse(&content_type)?;
+    // the strict grammar only applies to mint, spores minted under the legacy one are still
+    // transferable
+    let mime = MIME::parse_lenient(&content_type)?;
+
+    // check co-build action @lyk
+    let action::SporeActionUnion::TransferSpore(transfer) = extract_spore_action()?.to_enum()
+    else {
+        return Err(Error::SporeActionMismatch);
+    };
+    if transfer.spore_id().as_slice() != &load_self_id()? {
+        return Err(Error::SporeActionFieldMismatch);
+    }
+    check_spore_address(GroupInput, transfer.from())?;
+    check_spore_address(GroupOutput, transfer.to())?;
 
     if !mime.mutants.is_empty() {
         let spore_type = load_script()?;
@@ -220,110 +375,173 @@ fn process_transfer() -> Result<(), Error> {
         let output_index =
             find_position_by_type(&spore_type, Output).ok_or(Error::IndexOutOfBound)?;
         verify_extension(
+            &input_data,
             &mime,
             Operation::Transfer,
-            vec![input_index as u8, output_index as u8],
+            vec![input_index, output_index],
         )?;
     }
 
-    // check co-build action @lyk
-    let action::SporeActionUnion::TransferSpore(transfer) = extract_spore_action()?.to_enum()
-    else {
-        return Err(Error::SporeActionMismatch);
-    };
-    if transfer.spore_id().as_slice() != &load_self_id()? {
-        return Err(Error::SporeActionFieldMismatch);
-    }
-    check_spore_address(GroupInput, transfer.from())?;
-    check_spore_address(GroupOutput, transfer.to())?;
-
     Ok(())
 }
 
-fn verify_extension(mime: &MIME, op: Operation, argv: Vec<u8>) -> Result<(), Error> {
+// mutants run by the same extension
+struct MutantGroup {
+    // position of the extension in MUTANT_CODE_HASHES, i.e. the one each mutant is deployed with
+    extension_position: usize,
+    argv: Vec<Vec<u8>>,
+}
+
+// all applied mutants are executed in a single extension call with argv of
+// `[opcode, spore indices.., cluster_index, mutant_index_1, mutant_args_1, ..]`, mutants of another
+// extension follow `next` and the code hash of that extension, which is exec-ed in a chain
+//
+// note: exec never returns once succeeded, so it must be the last step of verification
+fn verify_extension(
+    spore_data: &SporeData,
+    mime: &MIME,
+    op: Operation,
+    argv: Vec<usize>,
+) -> Result<(), Error> {
+    let content_type = spore_data.content_type().raw_data();
+    // extension is unaware of cluster code hashes, so pass the cluster cell dep index to it,
+    // empty if spore is not in cluster mode
+    let cluster_index = spore_data
+        .cluster_id()
+        .to_opt()
+        .and_then(|cluster_id| {
+            find_position_by_type_args(
+                &cluster_id.raw_data(),
+                CellDep,
+                Some(check_cluster_code_hash),
+            )
+        })
+        .map(|index| format!("{index}"))
+        .unwrap_or_default();
     let mut payment_map: BTreeMap<[u8; 32], u64> = BTreeMap::new();
-    let mut extension_hash = [0u8; 32];
-    for mutant_id in mime.mutants.iter() {
-        let mutant_index =
-            QueryIter::new(load_cell_type, CellDep).position(|script| match script {
-                Some(script) => {
-                    extension_hash = script.code_hash().unpack();
-                    if crate::hash::MUTANT_CODE_HASHES.contains(&extension_hash) {
-                        return mutant_id[..] == script.args().raw_data()[..32];
-                    }
-                    false
-                }
-                None => false,
-            });
-        match mutant_index {
-            None => return Err(Error::ExtensionCellNotInDep),
-            Some(mutant_index) => {
-                // mint spore should pay if payment set
-                if let Operation::Mint = op {
-                    check_payment(mutant_index, &mut payment_map)?;
-                }
-
-                debug!("run mutant_id({mutant_index}): {mutant_id:?} <= {extension_hash:?}");
-                match op {
-                    Operation::Mint => {
-                        ckb_std::high_level::exec_cell(
-                            &extension_hash,
-                            ScriptHashType::Data1,
-                            &[
-                                CStr::from_bytes_with_nul([b'0', 0].as_slice()).unwrap_or_default(),
-                                CStr::from_bytes_with_nul([b'0' + mutant_index as u8, 0].as_slice(),) .unwrap_or_default(),
-                                CStr::from_bytes_with_nul([b'0' + argv[0], 0].as_slice()) .unwrap_or_default(),
-                            ],
-                        )?;
-                    }
-                    Operation::Transfer => {
-                        ckb_std::high_level::exec_cell(
-                            &extension_hash,
-                            ScriptHashType::Data1,
-                            &[
-                                CStr::from_bytes_with_nul([b'1', 0].as_slice()).unwrap_or_default(),
-                                CStr::from_bytes_with_nul([b'0' + mutant_index as u8, 0].as_slice(),) .unwrap_or_default(),
-                                CStr::from_bytes_with_nul([b'0' + argv[0], 0].as_slice()) .unwrap_or_default(),
-                                CStr::from_bytes_with_nul([b'0' + argv[1], 0].as_slice()) .unwrap_or_default(),
-                            ],
-                        )?;
-                    }
-                    Operation::Burn => {
-                        ckb_std::high_level::exec_cell(
-                            &extension_hash,
-                            ScriptHashType::Data1,
-                            &[
-                                CStr::from_bytes_with_nul([b'2', 0].as_slice()).unwrap_or_default(),
-                                CStr::from_bytes_with_nul([b'0' + mutant_index as u8, 0].as_slice(),) .unwrap_or_default(),
-                                CStr::from_bytes_with_nul([b'0' + argv[0], 0].as_slice()) .unwrap_or_default(),
-                            ],
-                        )?;
-                    }
-                }
+    // grouped by the exact extension code hash in the order of appearance, so that every mutant runs
+    // by the extension it's deployed with
+    let mut groups: Vec<MutantGroup> = Vec::new();
+    for (i, mutant_id) in mime.mutants.iter().enumerate() {
+        let (mutant_index, code_hash_position) = QueryIter::new(load_cell_type, CellDep)
+            .enumerate()
+            .find_map(|(index, script)| {
+                let script = script?;
+                let code_hash: [u8; 32] = script.code_hash().unpack();
+                let position = MUTANT_CODE_HASHES
+                    .iter()
+                    .position(|hash| hash == &code_hash)?;
+                let args = script.args().raw_data();
+                (args.get(..MUTANT_ID_LEN) == Some(&mutant_id[..])).then_some((index, position))
+            })
+            .ok_or(Error::ExtensionCellNotInDep)?;
+        let group = match groups
+            .iter()
+            .position(|group| group.extension_position == code_hash_position)
+        {
+            Some(group) => &mut groups[group],
+            None => {
+                groups.push(MutantGroup {
+                    extension_position: code_hash_position,
+                    argv: Vec::new(),
+                });
+                groups.last_mut().unwrap()
             }
+        };
+
+        // spore operation should pay if payment set
+        check_payment(mutant_index, &op, &mut payment_map)?;
+
+        // spore pinned to a version is only minted with that version of an updatable mutant, later
+        // operations run the current version, otherwise an update would lock the spore forever
+        if let (Operation::Mint, Some(pinned_version)) = (&op, mime.mutant_versions[i]) {
+            check_mutant_version(mutant_index, pinned_version)?;
         }
+
+        // per-spore args of this mutant, empty if not set
+        let mutant_args = mime
+            .mutant_args
+            .get(i)
+            .map(|range| content_type[range.clone()].to_vec())
+            .unwrap_or_default();
+        debug!("apply mutant_id({mutant_index}): {mutant_id:?}");
+        group.argv.push(format!("{mutant_index}").into_bytes());
+        group.argv.push(mutant_args);
+    }
+    let Some(first_group) = groups.first() else {
+        return Ok(());
+    };
+    let extension_hash = &MUTANT_CODE_HASHES[first_group.extension_position];
+    let mutants_argv = groups.iter().enumerate().flat_map(|(i, group)| {
+        let next = (i > 0).then(|| {
+            let code_hash = MUTANT_CODE_HASHES[group.extension_position]
+                .iter()
+                .map(|byte| format!("{byte:02x}"))
+                .collect::<String>();
+            [
+                MUTANT_ARGV_NEXT_EXTENSION.as_bytes().to_vec(),
+                code_hash.into_bytes(),
+            ]
+        });
+        next.into_iter().flatten().chain(group.argv.iter().cloned())
+    });
+
+    // indices are encoded in decimal, followed by cluster index and pairs of mutant index and args
+    let argv = [op.opcode()]
+        .iter()
+        .chain(&argv)
+        .map(|value| format!("{value}").into_bytes())
+        .chain([cluster_index.into_bytes()])
+        .chain(mutants_argv)
+        .map(|arg| CString::new(arg).map_err(|_| Error::InvalidParamValue))
+        .collect::<Result<Vec<_>, _>>()?;
+    let argv = argv.iter().map(CString::as_c_str).collect::<Vec<_>>();
+
+    debug!("run mutants by extension: {extension_hash:?}");
+    ckb_std::high_level::exec_cell(extension_hash, ScriptHashType::Data1, &argv)?;
+    Ok(())
+}
+
+fn check_mutant_version(mutant_index: usize, pinned_version: u64) -> Result<(), Error> {
+    let mutant_args = load_cell_type(mutant_index, CellDep)?
+        .unwrap_or_default()
+        .args()
+        .raw_data();
+    let mutant_data = load_cell_data(mutant_index, CellDep)?;
+    let (version, _) = split_mutant_data(&mutant_args, &mutant_data)?;
+    if version != Some(pinned_version) {
+        return Err(Error::MutantVersionMismatch);
     }
     Ok(())
 }
 
 fn check_payment(
     mutant_index: usize,
+    op: &Operation,
     payment_map: &mut BTreeMap<[u8; 32], u64>,
 ) -> Result<(), Error> {
     let mutant_type = load_cell_type(mutant_index, CellDep)?.unwrap_or_default();
     let args = mutant_type.args().raw_data();
-    // CAUTION: only check bytes in [32, 40) pattern, leave room for user customization
+    // CAUTION: only check bytes in [32, 40), the optional recipient in [40, 72) and transfer/burn
+    // payments in [72, 88) pattern, leave room for user customization
     if args.len() > MUTANT_ID_LEN {
         if args.len() < MUTANT_ID_WITH_PAYMENT_LEN {
             return Err(Error::InvalidExtensionPaymentFormat);
         }
-        let mutant_lock_hash = load_cell_lock_hash(mutant_index, CellDep)?;
-        let input_capacity = calc_capacity_sum(&mutant_lock_hash, Input);
-        let output_capacity = calc_capacity_sum(&mutant_lock_hash, Output);
+        // transfer and burn are free unless their payments are set
+        let threshold = match (op, mutant_payment(&args, op.opcode())) {
+            (_, Some(threshold)) if threshold > 0 => threshold,
+            (Operation::Mint, _) => 0,
+            _ => return Ok(()),
+        };
+        let recipient_lock_hash = match mutant_payment_recipient(&args)? {
+            Some(lock_hash) => lock_hash,
+            None => load_cell_lock_hash(mutant_index, CellDep)?,
+        };
+        let input_capacity = calc_capacity_sum(&recipient_lock_hash, Input);
+        let output_capacity = calc_capacity_sum(&recipient_lock_hash, Output);
         let minimal_payment = {
-            let range = MUTANT_ID_LEN..MUTANT_ID_WITH_PAYMENT_LEN;
-            let threshold = u64::from_le_bytes(args[range].try_into().unwrap_or_default());
-            let payment_threshold = payment_map.entry(mutant_lock_hash).or_default();
+            let payment_threshold = payment_map.entry(recipient_lock_hash).or_default();
             *payment_threshold += threshold;
             *payment_threshold
         };
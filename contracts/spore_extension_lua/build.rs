@@ -1,12 +1,34 @@
 use ckb_hash::blake2b_256;
 use std::{env, fs};
 
+const PAGE_SIZE: u64 = 4096;
+
+// memory span of all PT_LOAD segments in the ELF, plus a page for alignment
+fn elf_load_size(elf: &[u8]) -> u64 {
+    let read_u16 = |offset: usize| u16::from_le_bytes(elf[offset..offset + 2].try_into().unwrap());
+    let read_u32 = |offset: usize| u32::from_le_bytes(elf[offset..offset + 4].try_into().unwrap());
+    let read_u64 = |offset: usize| u64::from_le_bytes(elf[offset..offset + 8].try_into().unwrap());
+    let ph_offset = read_u64(0x20) as usize;
+    let ph_entry_size = read_u16(0x36) as usize;
+    let ph_count = read_u16(0x38) as usize;
+    let load_end = (0..ph_count)
+        .map(|i| ph_offset + i * ph_entry_size)
+        .filter(|&header| read_u32(header) == 1) // PT_LOAD
+        .map(|header| read_u64(header + 0x10) + read_u64(header + 0x28)) // p_vaddr + p_memsz
+        .max()
+        .expect("no loadable segment in libckblua.so");
+    load_end.div_ceil(PAGE_SIZE) * PAGE_SIZE + PAGE_SIZE
+}
+
 pub fn main() {
     let compile_mode = env::var("PROFILE").unwrap();
     let libckblua_path = env::current_dir().unwrap().join("lua/libckblua.so");
     let libckblua = std::fs::read(libckblua_path).expect("load libckblua.so");
     let code_hash = blake2b_256(&libckblua);
-    let file = format!("pub const CKB_LUA_LIB_CODE_HASH: [u8; 32] = {code_hash:?};\n");
+    let context_size = elf_load_size(&libckblua);
+    let file = format!(
+        "pub const CKB_LUA_LIB_CODE_HASH: [u8; 32] = {code_hash:?};\npub const CKB_LUA_LIB_CONTEXT_SIZE: usize = {context_size};\n"
+    );
     fs::write("./src/hash.rs", file).unwrap();
 
     let build_path = env::current_dir()
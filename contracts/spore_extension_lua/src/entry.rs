@@ -6,22 +6,36 @@ use alloc::borrow::ToOwned;
 // https://doc.rust-lang.org/alloc/index.html
 use alloc::ffi::CString;
 use alloc::string::String;
-use alloc::{format, vec, vec::Vec};
+use alloc::{format, vec::Vec};
 
 // Import CKB syscalls and structures
 // https://docs.rs/ckb-std/
-use ckb_std::ckb_constants::Source::{CellDep, GroupInput, GroupOutput, Output};
+use ckb_std::ckb_constants::Source::{self, CellDep, GroupInput, GroupOutput, Input, Output};
 use ckb_std::ckb_types::packed::Script;
+use ckb_std::ckb_types::prelude::*;
 use ckb_std::debug;
 use ckb_std::dynamic_loading_c_impl::{CKBDLContext, Library, Symbol};
 use ckb_std::env::Arg;
-use ckb_std::high_level::{load_cell_data, load_cell_type, QueryIter};
+use ckb_std::high_level::{load_cell_data, load_cell_type, load_script, QueryIter};
 use core::ffi::{c_char, c_int, c_ulong, c_void};
 use spore_errors::error::Error;
-use spore_utils::{find_position_by_type, verify_type_id};
+use spore_types::generated::spore::SporeData;
+use spore_utils::lua::{
+    check_ckb_api_usage, lua_memory_size, mutant_error_code, CKB_API_WHITELIST,
+    CKB_API_WHITELIST_PREFIXES, MUTANT_ENTRY_POINTS, MUTANT_ERROR_CODE_BASE, MUTANT_ERROR_CODE_MAX,
+    MUTANT_LIFECYCLE_ENTRY_POINTS,
+};
+use spore_utils::{
+    compatible_load_cluster_data, exec_next_extension, extract_mutant_code, find_position_by_type,
+    is_updatable_mutant, mutant_mode_offset, mutant_payment_recipient, split_mutant_data,
+    split_next_extension, verify_type_id, MIME, MUTANT_ID_WITH_OP_PAYMENTS_LEN,
+    MUTANT_ID_WITH_RECIPIENT_LEN, MUTANT_MODE_NORMAL, MUTANT_OP_AGENT_CREATION,
+    MUTANT_OP_CLUSTER_TRANSFER, MUTANT_OP_PROXY_CREATION, MUTANT_OP_SPORE_BURN,
+    MUTANT_OP_SPORE_MINT, MUTANT_OP_SPORE_TRANSFER,
+};
 
 use crate::error::WrappedError;
-use crate::hash::CKB_LUA_LIB_CODE_HASH;
+use crate::hash::{CKB_LUA_LIB_CODE_HASH, CKB_LUA_LIB_CONTEXT_SIZE};
 
 type CreateLuaInstanceType = unsafe extern "C" fn(c_ulong, c_ulong) -> *mut c_void;
 type EvaluateLuaInstanceType = unsafe extern "C" fn(
@@ -34,49 +48,93 @@ type EvaluateLuaInstanceType = unsafe extern "C" fn(
 const SPORE_EXT_NORMAL_ARG_LEN: usize = 32;
 const SPORE_EXT_MINIMAL_PAYMENT_ARG_LEN: usize = 32 + 8; // 32 bytes hash + u64 payment
 
-struct CKBLuaLib {
+// exit codes reserved for mutant validation
+const LUA_COMPILE_FAILED_CODE: i8 = 126;
+const LUA_ENTRY_POINT_MISSING_CODE: i8 = 127;
+
+// globals exposed to mutants, those able to reach the shared Lua state (e.g. `load`, `debug`,
+// `getmetatable`, `rawget` and `package`) are left out
+const MUTANT_SANDBOX_GLOBALS: [&str; 14] = [
+    "_VERSION",
+    "assert",
+    "error",
+    "ipairs",
+    "next",
+    "pairs",
+    "pcall",
+    "print",
+    "select",
+    "setmetatable",
+    "tonumber",
+    "tostring",
+    "type",
+    "xpcall",
+];
+
+// libraries exposed to mutants, each mutant receives its own copy of them, and `ckb` is copied
+// with constants and the whitelisted API only
+const MUTANT_SANDBOX_LIBS: [&str; 5] = ["coroutine", "math", "string", "table", "utf8"];
+
+// content larger than this won't be injected into Lua, mutant can load it via ckb syscalls instead
+const MAX_INJECTED_CONTENT_SIZE: usize = 4 * 1024;
+
+// Lua instance along with its heap, the memory is uninitialized since Lua never reads before writing
+struct LuaInstance {
+    instance: *mut c_void,
+    memory: Vec<u8>,
+}
+
+type CKBLuaLibContext = CKBDLContext<[u8; CKB_LUA_LIB_CONTEXT_SIZE]>;
+
+struct CKBLuaLib<'a> {
+    // loaded library lives in the context, so it's borrowed as long as the library
+    _context: &'a mut CKBLuaLibContext,
     lib: Library,
+    // reused by all scripts running on this library, and grown if a larger heap is required
+    instance: Option<LuaInstance>,
 }
 
-impl CKBLuaLib {
-    pub fn new() -> Result<Self, Error> {
+impl<'a> CKBLuaLib<'a> {
+    pub fn new(context: &'a mut CKBLuaLibContext) -> Result<Self, Error> {
         debug!("prepare lua lib");
-        let mut context = unsafe { CKBDLContext::<[u8; 270 * 1024]>::new() };
         #[allow(deprecated)]
         let lib = context
             .load(&CKB_LUA_LIB_CODE_HASH)
             .map_err(|_| Error::FailedToLoadLuaLib)?;
-        Ok(Self { lib })
+        Ok(Self {
+            _context: context,
+            lib,
+            instance: None,
+        })
     }
 
-    pub fn evaluate_lua_script(
-        &self,
-        index: usize,
-        prefix_code: Option<String>,
-    ) -> Result<(), WrappedError> {
-        let mut code_base = load_cell_data(index, Output)?;
-        if let Some(prefix_code) = prefix_code {
-            let mut prefix_code = prefix_code.as_bytes().to_vec();
-            prefix_code.append(&mut code_base);
-            code_base = prefix_code;
+    fn lua_instance(&mut self, memory_size: usize) -> Result<*mut c_void, Error> {
+        match &self.instance {
+            Some(lua) if lua.memory.capacity() >= memory_size => Ok(lua.instance),
+            _ => {
+                // release the smaller heap first to leave room for the new one
+                self.instance = None;
+                let lua = self.create_lua_instance(memory_size)?;
+                let instance = lua.instance;
+                self.instance = Some(lua);
+                Ok(instance)
+            }
         }
-        self.execute_lua_script(&code_base)?;
-        Ok(())
     }
 
-    fn create_lua_instance(&self) -> Result<*mut c_void, Error> {
+    fn create_lua_instance(&self, memory_size: usize) -> Result<LuaInstance, Error> {
         match unsafe { self.lib.get(b"lua_create_instance") } {
             Some(create_lua_instance) => {
-                let mut lua_mem = vec![0u8; 500 * 1024];
+                let mut memory = Vec::<u8>::with_capacity(memory_size);
                 unsafe {
                     let instance = (create_lua_instance as Symbol<CreateLuaInstanceType>)(
-                        lua_mem.as_mut_ptr() as c_ulong,
-                        lua_mem.as_mut_ptr().offset(500 * 1024) as c_ulong,
+                        memory.as_mut_ptr() as c_ulong,
+                        memory.as_mut_ptr().add(memory_size) as c_ulong,
                     );
                     if instance.is_null() {
                         return Err(Error::FailedToCreateLuaInstance);
                     }
-                    Ok(instance)
+                    Ok(LuaInstance { instance, memory })
                 }
             }
             None => {
@@ -86,9 +144,19 @@ impl CKBLuaLib {
         }
     }
 
-    pub fn execute_lua_script(&self, code: &Vec<u8>) -> Result<(), WrappedError> {
-        let instance = self.create_lua_instance()?;
-        let ret = match unsafe { self.lib.get(b"lua_run_code") } {
+    pub fn execute_lua_script(
+        &mut self,
+        code: &Vec<u8>,
+        memory_size: usize,
+    ) -> Result<(), WrappedError> {
+        let ret = self.run_lua_script(code, memory_size)?;
+        lua_exit_result(ret)
+    }
+
+    // returns the exit code of Lua script as it is
+    fn run_lua_script(&mut self, code: &Vec<u8>, memory_size: usize) -> Result<i8, Error> {
+        let instance = self.lua_instance(memory_size)?;
+        match unsafe { self.lib.get(b"lua_run_code") } {
             Some(lua_run_code) => {
                 let size = code.len().clone();
                 let ret = unsafe {
@@ -102,20 +170,101 @@ impl CKBLuaLib {
                 Ok(ret as i8)
             }
             None => Err(Error::InvalidLuaLib),
-        }?;
+        }
+    }
 
-        if ret == 0 {
-            return Ok(());
+    // validate mutant code before it can be referenced, the code is compiled alone first to tell
+    // syntax errors apart from failures of the code itself, and a chunk that compiles alone can't
+    // break out of the wrapper in `wrap_mutant_code` either
+    pub fn validate_mutant_code(
+        &mut self,
+        code: &[u8],
+        memory_size: usize,
+    ) -> Result<(), WrappedError> {
+        check_ckb_api_usage(code)?;
+        let compile_check = format!(
+            "if load({}, \"SporeExtension\", \"t\", {{}}) == nil then ckb.exit_script({LUA_COMPILE_FAILED_CODE}) end\n",
+            to_lua_string(code)
+        );
+        match self.run_lua_script(&compile_check.into_bytes(), memory_size)? {
+            0 => {}
+            LUA_COMPILE_FAILED_CODE => return Err(Error::LuaCompileFailed.into()),
+            ret => return lua_exit_result(ret),
         }
-        // we recommend the error code follows this pattern: -127 <= ret <= -1 or 100 <= ret <= 127
-        else if 0 < ret && ret < Error::Unknown as i8 {
-            return Err(Error::InvalidLuaScript.into());
-        } else {
-            return Err(WrappedError::LuaError(ret));
+
+        // then the top level runs as in execution, with stub values of mode 0
+        let entry_points_check: String = MUTANT_ENTRY_POINTS
+            .iter()
+            .map(|name| {
+                format!(
+                    "if type(spore_ext_env.{name}) ~= \"function\" then ckb.exit_script({LUA_ENTRY_POINT_MISSING_CODE}) end\n"
+                )
+            })
+            .collect();
+        let validator = wrap_mutant_code(&validation_prefix_code(), code, &entry_points_check);
+        match self.run_lua_script(&validator, memory_size)? {
+            0 => Ok(()),
+            LUA_ENTRY_POINT_MISSING_CODE => Err(Error::LuaEntryPointMissing.into()),
+            ret => lua_exit_result(ret),
         }
     }
 }
 
+// application errors of mutant are kept as they are, and codes overlapped with contract errors are
+// regarded as failures of Lua script
+fn lua_exit_result(ret: i8) -> Result<(), WrappedError> {
+    if ret == 0 {
+        Ok(())
+    } else if let Some(code) = mutant_error_code(ret) {
+        Err(WrappedError::MutantError(code))
+    } else if 0 < ret && ret < MUTANT_ERROR_CODE_BASE {
+        Err(Error::InvalidLuaScript.into())
+    } else {
+        Err(WrappedError::LuaError(ret))
+    }
+}
+
+// `spore_error(code)` helper for mutants to report application errors in the reserved range
+fn spore_error_helper() -> String {
+    format!(
+        "function spore_error(code)\n  if math.type(code) ~= \"integer\" or code < 0 or code > {MUTANT_ERROR_CODE_MAX} then error(\"invalid spore error code\") end\n  ckb.exit_script({MUTANT_ERROR_CODE_BASE} + code)\nend\n"
+    )
+}
+
+// load Lua code and heap size of mutant, version prefix of updatable mutant and metadata are excluded
+fn load_mutant(index: usize, source: Source) -> Result<(Vec<u8>, usize), WrappedError> {
+    let args = load_cell_type(index, source)?
+        .unwrap_or_default()
+        .args()
+        .raw_data();
+    let data = load_cell_data(index, source)?;
+    let (_, data) = split_mutant_data(&args, &data)?;
+    let code = extract_mutant_code(data)?;
+    Ok((code.to_vec(), lua_memory_size(&args)?))
+}
+
+// `spore_ext_sandbox()` helper to build a fresh global environment for each mutant, so globals
+// patched by one mutant (e.g. `ckb.exit_script`) never reach others sharing the Lua instance
+fn sandbox_helper() -> String {
+    let to_lua_names =
+        |names: &[&str]| -> String { names.iter().map(|name| format!("\"{name}\", ")).collect() };
+    let ckb_api_check: String = CKB_API_WHITELIST
+        .iter()
+        .map(|name| format!(" or key == \"{name}\""))
+        .chain(CKB_API_WHITELIST_PREFIXES.iter().map(|prefix| {
+            format!(
+                " or (#key > {len} and string.sub(key, 1, {len}) == \"{prefix}\")",
+                len = prefix.len()
+            )
+        }))
+        .collect();
+    format!(
+        "function spore_ext_sandbox()\n  local env = {{ spore_error = spore_error, ckb = {{}} }}\n  for _, name in ipairs({{ {}}}) do env[name] = _ENV[name] end\n  for _, name in ipairs({{ {}}}) do\n    if type(_ENV[name]) == \"table\" then\n      env[name] = {{}}\n      for key, value in pairs(_ENV[name]) do env[name][key] = value end\n    end\n  end\n  for key, value in pairs(ckb) do\n    if type(value) ~= \"function\"{ckb_api_check} then env.ckb[key] = value end\n  end\n  return env\nend\n",
+        to_lua_names(&MUTANT_SANDBOX_GLOBALS),
+        to_lua_names(&MUTANT_SANDBOX_LIBS)
+    )
+}
+
 fn process_creation(index: usize) -> Result<(), WrappedError> {
     if verify_type_id(index).is_none() {
         return Err(Error::InvalidExtensionID.into());
@@ -124,16 +273,23 @@ fn process_creation(index: usize) -> Result<(), WrappedError> {
         .unwrap_or_default()
         .args()
         .raw_data();
+    let mode_offset = mutant_mode_offset(&args);
     match args.len() {
-        SPORE_EXT_NORMAL_ARG_LEN | SPORE_EXT_MINIMAL_PAYMENT_ARG_LEN => {}
+        SPORE_EXT_NORMAL_ARG_LEN
+        | SPORE_EXT_MINIMAL_PAYMENT_ARG_LEN
+        | MUTANT_ID_WITH_RECIPIENT_LEN
+        | MUTANT_ID_WITH_OP_PAYMENTS_LEN => {}
+        _ if is_updatable_mutant(&args) => {}
+        len if len == mode_offset + 2 && args[mode_offset] == MUTANT_MODE_NORMAL => {}
         _ => {
             return Err(Error::InvalidExtensionArg.into());
         }
     }
-    let lua_lib = CKBLuaLib::new()?;
-
-    let prefix_code = "local spore_ext_mode = 0\n".to_owned();
-    lua_lib.evaluate_lua_script(index, Some(prefix_code))?;
+    mutant_payment_recipient(&args)?;
+    let (code, memory_size) = load_mutant(index, Output)?;
+    let mut context = unsafe { CKBLuaLibContext::new() };
+    let mut lua_lib = CKBLuaLib::new(&mut context)?;
+    lua_lib.validate_mutant_code(&code, memory_size)?;
     Ok(())
 }
 
@@ -141,49 +297,297 @@ fn process_transfer() -> Result<(), WrappedError> {
     let input_data = load_cell_data(0, GroupInput)?;
     let output_data = load_cell_data(0, GroupOutput)?;
 
-    if input_data != output_data {
+    if input_data == output_data {
+        return Ok(());
+    }
+
+    // only updatable mutant can publish new code, with version increased by one
+    let script = load_script()?;
+    let args = script.args().raw_data();
+    let (Some(input_version), _) = split_mutant_data(&args, &input_data)? else {
         return Err(Error::ModifyExtensionPermanentField.into());
+    };
+    let (output_version, _) = split_mutant_data(&args, &output_data)?;
+    if output_version != input_version.checked_add(1) {
+        return Err(Error::InvalidMutantVersion.into());
+    }
+
+    // new code must be valid as well as in creation
+    let output_index = find_position_by_type(&script, Output).unwrap_or_default();
+    let (code, memory_size) = load_mutant(output_index, Output)?;
+    let mut context = unsafe { CKBLuaLibContext::new() };
+    let mut lua_lib = CKBLuaLib::new(&mut context)?;
+    lua_lib.validate_mutant_code(&code, memory_size)?;
+    Ok(())
+}
+
+// escape every byte to avoid breaking Lua syntax, e.g. "\097\098"
+fn to_lua_string(bytes: &[u8]) -> String {
+    let escaped: String = bytes.iter().map(|byte| format!("\\{byte:03}")).collect();
+    format!("\"{escaped}\"")
+}
+
+fn to_lua_string_or_nil(bytes: Option<&[u8]>) -> String {
+    bytes.map(to_lua_string).unwrap_or("nil".to_owned())
+}
+
+// pre-decode spore and cluster data into Lua tables, so mutants don't need to parse molecule
+fn build_lua_tables(
+    spore_index: usize,
+    source: Source,
+    cluster_index: Option<usize>,
+) -> Result<String, WrappedError> {
+    let raw_spore_data = load_cell_data(spore_index, source)?;
+    let spore_data =
+        SporeData::from_compatible_slice(&raw_spore_data).map_err(|_| Error::InvalidSporeData)?;
+    let content_type = spore_data.content_type().raw_data();
+    // minted spores have been checked against the strict grammar by the spore contract already,
+    // existing ones may be minted under the legacy one
+    let mime = MIME::parse_lenient(&content_type)?;
+    // values are exposed without quotes
+    let params = mime
+        .params()
+        .iter()
+        .map(|(name, value)| {
+            Ok(format!(
+                "[{}] = {}, ",
+                to_lua_string(content_type.get(name.clone()).unwrap_or_default()),
+                to_lua_string(&MIME::unquote(&content_type, value)?)
+            ))
+        })
+        .collect::<Result<String, Error>>()?;
+    let content = spore_data.content().raw_data();
+    let injected_content = if content.len() <= MAX_INJECTED_CONTENT_SIZE {
+        to_lua_string(&content)
+    } else {
+        "nil".to_owned()
+    };
+    let cluster_id = spore_data.cluster_id().to_opt().map(|id| id.raw_data());
+    let mut tables = format!(
+        "local spore_data = {{ content_type = {}, main_type = {}, sub_type = {}, params = {{ {params}}}, content_size = {}, content = {injected_content}, cluster_id = {} }}\n",
+        to_lua_string(&content_type),
+        to_lua_string(content_type.get(mime.main_type.clone()).unwrap_or_default()),
+        to_lua_string(content_type.get(mime.sub_type.clone()).unwrap_or_default()),
+        content.len(),
+        to_lua_string_or_nil(cluster_id.as_deref()),
+    );
+
+    let cluster = match cluster_index {
+        Some(cluster_index) => build_cluster_lua_table(cluster_index, CellDep)?,
+        None => "nil".to_owned(),
+    };
+    tables += &format!("local cluster_data = {cluster}\n");
+    Ok(tables)
+}
+
+fn build_cluster_lua_table(cluster_index: usize, source: Source) -> Result<String, WrappedError> {
+    let raw_cluster_data = load_cell_data(cluster_index, source)?;
+    let cluster_data = compatible_load_cluster_data(&raw_cluster_data)?;
+    Ok(format!(
+        "{{ name = {}, description = {} }}",
+        to_lua_string(&cluster_data.name().raw_data()),
+        to_lua_string(&cluster_data.description().raw_data())
+    ))
+}
+
+fn parse_index(arg: &Arg) -> Result<usize, Error> {
+    arg.to_string_lossy()
+        .parse::<usize>()
+        .map_err(|_| Error::InvalidLuaParameters)
+}
+
+// optional index is passed as an empty string if not exists
+fn parse_optional_index(arg: Option<&Arg>) -> Result<Option<usize>, Error> {
+    match arg {
+        Some(arg) if !arg.to_bytes().is_empty() => Ok(Some(parse_index(arg)?)),
+        _ => Ok(None),
+    }
+}
+
+// mutants are passed in pairs of mutant index and args, e.g. `[index_1, args_1, index_2, args_2]`,
+// which may be followed by mutants of the next extension
+fn parse_mutants(argv: &[Arg]) -> Result<(Vec<(usize, &[u8])>, Option<&[Arg]>), Error> {
+    let (argv, next_argv) = split_next_extension(argv);
+    if argv.is_empty() || argv.len() % 2 != 0 {
+        return Err(Error::InvalidLuaParameters);
+    }
+    let mutants = argv
+        .chunks(2)
+        .map(|pair| Ok((parse_index(&pair[0])?, pair[1].to_bytes())))
+        .collect::<Result<_, Error>>()?;
+    Ok((mutants, next_argv))
+}
+
+// mutants of other extensions are left to the next one, which takes over the fixed argv
+fn run_next_extension(fixed_argv: &[Arg], next_argv: Option<&[Arg]>) -> Result<(), Error> {
+    match next_argv {
+        Some(next_argv) => exec_next_extension(fixed_argv, next_argv),
+        None => Ok(()),
     }
+}
+
+// mutant code is wrapped into a function to keep its top-level statements working, and runs under
+// its own sandbox environment, followed by `epilogue` which can reach the globals of mutant in
+// `spore_ext_env`, so validation and execution see the code in the same way
+fn wrap_mutant_code(prefix_code: &str, code: &[u8], epilogue: &str) -> Vec<u8> {
+    let mut script = spore_error_helper().into_bytes();
+    script.append(&mut sandbox_helper().into_bytes());
+    script.extend_from_slice(prefix_code.as_bytes());
+    script.extend_from_slice(
+        b"local spore_ext_env = spore_ext_sandbox()\nlocal spore_ext_chunk = function(...) local _ENV = spore_ext_env\n",
+    );
+    script.extend_from_slice(code);
+    script.extend_from_slice(b"\nend\nspore_ext_chunk()\n");
+    script.extend_from_slice(epilogue.as_bytes());
+    script
+}
+
+// stub values of all external values in mode 0, so top-level code reading them in validation behaves
+// as in execution, except that nothing is loaded from the transaction
+fn validation_prefix_code() -> String {
+    let indices: String = [
+        "spore_input_index",
+        "spore_output_index",
+        "cluster_input_index",
+        "cluster_output_index",
+        "cluster_dep_index",
+        "proxy_output_index",
+        "proxy_dep_index",
+        "agent_output_index",
+    ]
+    .iter()
+    .map(|name| format!("local {name} = 0\n"))
+    .collect();
+    format!(
+        "local spore_ext_mode = 0\n{indices}local spore_data = {{ content_type = \"\", main_type = \"\", sub_type = \"\", params = {{}}, content_size = 0, content = \"\", cluster_id = nil }}\nlocal cluster_data = {{ name = \"\", description = \"\" }}\nlocal spore_ext_args = \"\"\n"
+    )
+}
+
+// the entry point of current mode will be called if declared
+fn execute_code(
+    lua_lib: &mut CKBLuaLib<'_>,
+    extension_index: usize,
+    prefix_code: String,
+    entry_point: &str,
+) -> Result<(), WrappedError> {
+    let (code, memory_size) = load_mutant(extension_index, CellDep)?;
+    let epilogue = format!(
+        "if type(spore_ext_env.{entry_point}) == \"function\" then spore_ext_env.{entry_point}() end\n"
+    );
+    let script = wrap_mutant_code(&prefix_code, &code, &epilogue);
+    lua_lib.execute_lua_script(&script, memory_size)
+}
 
+// run every applied mutant in turn, with the shared prefix code followed by its own args
+fn execute_mutants(
+    lua_lib: &mut CKBLuaLib<'_>,
+    mutants: &[(usize, &[u8])],
+    prefix_code: String,
+    entry_point: &str,
+) -> Result<(), WrappedError> {
+    for (extension_index, args) in mutants {
+        let prefix_code = format!(
+            "{prefix_code}local spore_ext_args = {}\n",
+            to_lua_string(args)
+        );
+        execute_code(lua_lib, *extension_index, prefix_code, entry_point)?;
+    }
     Ok(())
 }
 
-fn execute_code_create(extension_index: usize, target_index: usize) -> Result<(), WrappedError> {
-    let mut code_base =
-        format!("local spore_ext_mode = 1\nlocal spore_output_index = {target_index}\n")
-            .as_bytes()
-            .to_vec();
-    let mut ext_code = load_cell_data(extension_index, CellDep)?;
-    code_base.append(&mut ext_code);
-    let lua_lib = CKBLuaLib::new()?;
-    lua_lib.execute_lua_script(&code_base)
+fn execute_code_create(
+    lua_lib: &mut CKBLuaLib<'_>,
+    mutants: &[(usize, &[u8])],
+    target_index: usize,
+    cluster_index: Option<usize>,
+) -> Result<(), WrappedError> {
+    let prefix_code = format!(
+        "local spore_ext_mode = 1\nlocal spore_output_index = {target_index}\n{}",
+        build_lua_tables(target_index, Output, cluster_index)?
+    );
+    execute_mutants(lua_lib, mutants, prefix_code, MUTANT_ENTRY_POINTS[0])
 }
 
 fn execute_code_transfer(
+    lua_lib: &mut CKBLuaLib<'_>,
+    mutants: &[(usize, &[u8])],
+    input_index: usize,
+    output_index: usize,
+    cluster_index: Option<usize>,
+) -> Result<(), WrappedError> {
+    let prefix_code = format!(
+        "local spore_ext_mode = 2\nlocal spore_input_index = {input_index}\nlocal spore_output_index = {output_index}\n{}",
+        build_lua_tables(output_index, Output, cluster_index)?
+    );
+    execute_mutants(lua_lib, mutants, prefix_code, MUTANT_ENTRY_POINTS[1])
+}
+
+fn execute_code_destroy(
+    lua_lib: &mut CKBLuaLib<'_>,
+    mutants: &[(usize, &[u8])],
+    input_index: usize,
+    cluster_index: Option<usize>,
+) -> Result<(), WrappedError> {
+    let prefix_code = format!(
+        "local spore_ext_mode = 3\nlocal spore_input_index = {input_index}\n{}",
+        build_lua_tables(input_index, Input, cluster_index)?
+    );
+    execute_mutants(lua_lib, mutants, prefix_code, MUTANT_ENTRY_POINTS[2])
+}
+
+fn execute_code_cluster_transfer(
+    lua_lib: &mut CKBLuaLib<'_>,
     extension_index: usize,
     input_index: usize,
     output_index: usize,
 ) -> Result<(), WrappedError> {
-    let mut code_base = format!(
-        "local spore_ext_mode = 2\nlocal spore_input_index = {input_index}\nlocal spore_output_index = {output_index}\n"
+    let prefix_code = format!(
+        "local spore_ext_mode = 4\nlocal cluster_input_index = {input_index}\nlocal cluster_output_index = {output_index}\nlocal cluster_data = {}\n",
+        build_cluster_lua_table(input_index, Input)?
+    );
+    execute_code(
+        lua_lib,
+        extension_index,
+        prefix_code,
+        MUTANT_LIFECYCLE_ENTRY_POINTS[0],
     )
-    .as_bytes()
-    .to_vec();
-    let mut ext_code = load_cell_data(extension_index, CellDep)?;
-    code_base.append(&mut ext_code);
-    let lua_lib = CKBLuaLib::new()?;
-    lua_lib.execute_lua_script(&code_base)
 }
 
-fn execute_code_destroy(extension_index: usize, input_index: usize) -> Result<(), WrappedError> {
-    let mut code_base =
-        format!("local spore_ext_mode = 3\nlocal spore_input_index = {input_index}\n")
-            .as_bytes()
-            .to_vec();
-    let mut ext_code = load_cell_data(extension_index, CellDep)?;
-    code_base.append(&mut ext_code);
-    let lua_lib = CKBLuaLib::new()?;
-    lua_lib.execute_lua_script(&code_base)
+fn execute_code_proxy_create(
+    lua_lib: &mut CKBLuaLib<'_>,
+    extension_index: usize,
+    cluster_index: usize,
+    proxy_index: usize,
+) -> Result<(), WrappedError> {
+    let prefix_code = format!(
+        "local spore_ext_mode = 5\nlocal cluster_dep_index = {cluster_index}\nlocal proxy_output_index = {proxy_index}\nlocal cluster_data = {}\n",
+        build_cluster_lua_table(cluster_index, CellDep)?
+    );
+    execute_code(
+        lua_lib,
+        extension_index,
+        prefix_code,
+        MUTANT_LIFECYCLE_ENTRY_POINTS[1],
+    )
+}
+
+fn execute_code_agent_create(
+    lua_lib: &mut CKBLuaLib<'_>,
+    extension_index: usize,
+    cluster_index: usize,
+    proxy_index: usize,
+    agent_index: usize,
+) -> Result<(), WrappedError> {
+    let prefix_code = format!(
+        "local spore_ext_mode = 6\nlocal cluster_dep_index = {cluster_index}\nlocal proxy_dep_index = {proxy_index}\nlocal agent_output_index = {agent_index}\nlocal cluster_data = {}\n",
+        build_cluster_lua_table(cluster_index, CellDep)?
+    );
+    execute_code(
+        lua_lib,
+        extension_index,
+        prefix_code,
+        MUTANT_LIFECYCLE_ENTRY_POINTS[2],
+    )
 }
 
 pub fn main(argv: &[Arg]) -> Result<(), WrappedError> {
@@ -221,49 +625,83 @@ pub fn main(argv: &[Arg]) -> Result<(), WrappedError> {
     } else {
         // execution mode
         debug!("running externally");
-        match argv[0].to_bytes() {
-            &[48] => {
+        let mut context = unsafe { CKBLuaLibContext::new() };
+        let mut lua_lib = CKBLuaLib::new(&mut context)?;
+        match parse_index(&argv[0])? {
+            MUTANT_OP_SPORE_MINT => {
                 // 0, CREATE SPORE
                 debug!("Spore Creation with extension!");
-                let spore_extension_index = argv[1]
-                    .to_string_lossy()
-                    .parse::<usize>()
-                    .map_err(|_| Error::InvalidLuaParameters)?;
-                let target_index = argv[2]
-                    .to_string_lossy()
-                    .parse::<usize>()
-                    .map_err(|_| Error::InvalidLuaParameters)?;
-                execute_code_create(spore_extension_index, target_index)?;
+                let target_index = parse_index(&argv[1])?;
+                let cluster_index = parse_optional_index(argv.get(2))?;
+                let (mutants, next_argv) = parse_mutants(argv.get(3..).unwrap_or_default())?;
+                execute_code_create(&mut lua_lib, &mutants, target_index, cluster_index)?;
+                run_next_extension(&argv[..3], next_argv)?;
             }
-            &[49] => {
+            MUTANT_OP_SPORE_TRANSFER => {
                 // 1, TRANSFER SPORE
                 debug!("Spore Transfer with extension!");
-                let spore_extension_index = argv[1]
-                    .to_string_lossy()
-                    .parse::<usize>()
-                    .map_err(|_| Error::InvalidLuaParameters)?;
-                let input_index = argv[2]
-                    .to_string_lossy()
-                    .parse::<usize>()
-                    .map_err(|_| Error::InvalidLuaParameters)?;
-                let output_index = argv[3]
-                    .to_string_lossy()
-                    .parse::<usize>()
-                    .map_err(|_| Error::InvalidLuaParameters)?;
-                execute_code_transfer(spore_extension_index, input_index, output_index)?;
+                let input_index = parse_index(&argv[1])?;
+                let output_index = parse_index(&argv[2])?;
+                let cluster_index = parse_optional_index(argv.get(3))?;
+                let (mutants, next_argv) = parse_mutants(argv.get(4..).unwrap_or_default())?;
+                execute_code_transfer(
+                    &mut lua_lib,
+                    &mutants,
+                    input_index,
+                    output_index,
+                    cluster_index,
+                )?;
+                run_next_extension(&argv[..4], next_argv)?;
             }
-            &[50] => {
+            MUTANT_OP_SPORE_BURN => {
                 // 2, DESTROY SPORE
                 debug!("Spore Destroy with extension!");
-                let spore_extension_index = argv[1]
-                    .to_string_lossy()
-                    .parse::<usize>()
-                    .map_err(|_| Error::InvalidLuaParameters)?;
-                let input_index = argv[2]
-                    .to_string_lossy()
-                    .parse::<usize>()
-                    .map_err(|_| Error::InvalidLuaParameters)?;
-                execute_code_destroy(spore_extension_index, input_index)?;
+                let input_index = parse_index(&argv[1])?;
+                let cluster_index = parse_optional_index(argv.get(2))?;
+                let (mutants, next_argv) = parse_mutants(argv.get(3..).unwrap_or_default())?;
+                execute_code_destroy(&mut lua_lib, &mutants, input_index, cluster_index)?;
+                run_next_extension(&argv[..3], next_argv)?;
+            }
+            MUTANT_OP_CLUSTER_TRANSFER => {
+                // 3, TRANSFER CLUSTER
+                debug!("Cluster Transfer with extension!");
+                let cluster_extension_index = parse_index(&argv[1])?;
+                let input_index = parse_index(&argv[2])?;
+                let output_index = parse_index(&argv[3])?;
+                execute_code_cluster_transfer(
+                    &mut lua_lib,
+                    cluster_extension_index,
+                    input_index,
+                    output_index,
+                )?;
+            }
+            MUTANT_OP_PROXY_CREATION => {
+                // 4, CREATE CLUSTER PROXY
+                debug!("Cluster Proxy Creation with extension!");
+                let cluster_extension_index = parse_index(&argv[1])?;
+                let cluster_index = parse_index(&argv[2])?;
+                let proxy_index = parse_index(&argv[3])?;
+                execute_code_proxy_create(
+                    &mut lua_lib,
+                    cluster_extension_index,
+                    cluster_index,
+                    proxy_index,
+                )?;
+            }
+            MUTANT_OP_AGENT_CREATION => {
+                // 5, CREATE CLUSTER AGENT
+                debug!("Cluster Agent Creation with extension!");
+                let cluster_extension_index = parse_index(&argv[1])?;
+                let cluster_index = parse_index(&argv[2])?;
+                let proxy_index = parse_index(&argv[3])?;
+                let agent_index = parse_index(&argv[4])?;
+                execute_code_agent_create(
+                    &mut lua_lib,
+                    cluster_extension_index,
+                    cluster_index,
+                    proxy_index,
+                    agent_index,
+                )?;
             }
             _ => return Err(Error::InvalidExtensionOperation.into()),
         }
@@ -1,9 +1,12 @@
 use ckb_std::syscalls::SysError;
 use spore_errors::error::Error;
+use spore_utils::lua::MUTANT_ERROR_CODE_BASE;
 
 pub enum WrappedError {
     SystemError(Error),
     LuaError(i8),
+    // application error reported by `spore_error(code)` of mutant
+    MutantError(u8),
 }
 
 impl From<WrappedError> for i8 {
@@ -11,6 +14,7 @@ impl From<WrappedError> for i8 {
         match value {
             WrappedError::SystemError(error) => error as i8,
             WrappedError::LuaError(error) => error,
+            WrappedError::MutantError(code) => MUTANT_ERROR_CODE_BASE + code as i8,
         }
     }
 }
@@ -20,7 +20,8 @@ use ckb_std::default_alloc;
 #[cfg(not(test))]
 ckb_std::entry!(program_entry);
 #[cfg(not(test))]
-default_alloc!(6 * 1024, 800 * 1024, 64);
+// big heap holds a Lua instance of the largest memory tier along with the code to run
+default_alloc!(6 * 1024, 2048 * 1024, 64);
 
 /// program entry
 pub fn program_entry() -> i8 {
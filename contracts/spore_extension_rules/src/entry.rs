@@ -0,0 +1,180 @@
+// Import from `core` instead of from `std` since we are in no-std mode
+use core::result::Result;
+
+// Import heap related library from `alloc`
+// https://doc.rust-lang.org/alloc/index.html
+use alloc::vec::Vec;
+
+// Import CKB syscalls and structures
+// https://docs.rs/ckb-std/
+use ckb_std::ckb_constants::Source::{self, CellDep, GroupInput, GroupOutput, Input, Output};
+use ckb_std::ckb_types::packed::Script;
+use ckb_std::ckb_types::prelude::*;
+use ckb_std::debug;
+use ckb_std::env::Arg;
+use ckb_std::high_level::{
+    load_cell_data, load_cell_lock, load_cell_type, load_input_since, QueryIter,
+};
+use spore_errors::error::Error;
+use spore_types::generated::mutant_rules::MutantRules;
+use spore_utils::{
+    exec_next_extension, find_position_by_type, is_relative_since, is_relative_since_satisfied,
+    split_next_extension, verify_type_id, MUTANT_ID_LEN, MUTANT_ID_WITH_OP_PAYMENTS_LEN,
+    MUTANT_ID_WITH_PAYMENT_LEN, MUTANT_ID_WITH_RECIPIENT_LEN, MUTANT_OP_SPORE_BURN,
+    MUTANT_OP_SPORE_MINT, MUTANT_OP_SPORE_TRANSFER,
+};
+
+fn load_rules(index: usize, source: Source) -> Result<MutantRules, Error> {
+    let raw_rules = load_cell_data(index, source)?;
+    MutantRules::from_compatible_slice(&raw_rules).map_err(|_| Error::InvalidMutantRules)
+}
+
+fn process_creation(index: usize) -> Result<(), Error> {
+    if verify_type_id(index).is_none() {
+        return Err(Error::InvalidExtensionID);
+    }
+    let args = load_cell_type(index, Output)?
+        .unwrap_or_default()
+        .args()
+        .raw_data();
+    if !matches!(
+        args.len(),
+        MUTANT_ID_LEN
+            | MUTANT_ID_WITH_PAYMENT_LEN
+            | MUTANT_ID_WITH_RECIPIENT_LEN
+            | MUTANT_ID_WITH_OP_PAYMENTS_LEN
+    ) {
+        return Err(Error::InvalidExtensionArg);
+    }
+
+    // holding period only makes sense in relative since
+    let rules = load_rules(index, Output)?;
+    if let Some(holding_period) = rules.holding_period().to_opt() {
+        if !is_relative_since(holding_period.unpack()) {
+            return Err(Error::InvalidMutantRules);
+        }
+    }
+    Ok(())
+}
+
+fn process_transfer() -> Result<(), Error> {
+    // rules are permanent once created, as well as the code of normal Lua mutants
+    let input_data = load_cell_data(0, GroupInput)?;
+    let output_data = load_cell_data(0, GroupOutput)?;
+    if input_data != output_data {
+        return Err(Error::ModifyExtensionPermanentField);
+    }
+    Ok(())
+}
+
+fn verify_rules(
+    rules_index: usize,
+    opcode: usize,
+    input_index: Option<usize>,
+    output_index: Option<usize>,
+) -> Result<(), Error> {
+    let rules = load_rules(rules_index, CellDep)?;
+
+    let allowed_operations: u8 = rules.allowed_operations().into();
+    if allowed_operations & (1 << opcode) == 0 {
+        return Err(Error::RulesOperationForbidden);
+    }
+
+    let recipient_lock_code_hashes = rules.recipient_lock_code_hashes();
+    if let Some(output_index) = output_index {
+        if !recipient_lock_code_hashes.is_empty() {
+            let code_hash = load_cell_lock(output_index, Output)?.code_hash();
+            let allowed = recipient_lock_code_hashes
+                .into_iter()
+                .any(|allowed_code_hash| allowed_code_hash.as_slice() == code_hash.as_slice());
+            if !allowed {
+                return Err(Error::RulesRecipientNotAllowed);
+            }
+        }
+    }
+
+    if let (Some(input_index), Some(holding_period)) =
+        (input_index, rules.holding_period().to_opt())
+    {
+        let since = load_input_since(input_index, Input)?;
+        if !is_relative_since_satisfied(since, holding_period.unpack()) {
+            return Err(Error::RulesHoldingPeriodNotMet);
+        }
+    }
+    Ok(())
+}
+
+// mutants are passed in pairs of mutant index and args, args are unused by rules
+fn parse_rules_indices(argv: &[Arg]) -> Result<Vec<usize>, Error> {
+    if argv.is_empty() || argv.len() % 2 != 0 {
+        return Err(Error::InvalidExtensionOperation);
+    }
+    argv.iter()
+        .step_by(2)
+        .map(|arg| parse_index(arg).ok_or(Error::InvalidExtensionOperation))
+        .collect()
+}
+
+fn parse_index(arg: &Arg) -> Option<usize> {
+    arg.to_string_lossy().parse::<usize>().ok()
+}
+
+pub fn main(argv: &[Arg]) -> Result<(), Error> {
+    if argv.is_empty() {
+        debug!("running internally");
+        // creation/transfer mode
+        let rules_in_output: Vec<Script> = QueryIter::new(load_cell_type, GroupOutput)
+            .map(|script| script.unwrap_or_default())
+            .collect();
+        if rules_in_output.len() > 1 {
+            return Err(Error::ConflictExtensionCreation);
+        }
+
+        let rules_in_input: Vec<Script> = QueryIter::new(load_cell_type, GroupInput)
+            .map(|script| script.unwrap_or_default())
+            .collect();
+        if rules_in_input.len() > 1 {
+            return Err(Error::ExtensionMultipleSpend);
+        }
+
+        return match (rules_in_input.len(), rules_in_output.len()) {
+            (0, 1) => {
+                let output_index =
+                    find_position_by_type(&rules_in_output[0], Output).unwrap_or_default();
+                process_creation(output_index)
+            }
+            (1, 1) => process_transfer(),
+            _ => Err(Error::InvalidExtensionOperation), // Can not destroy a extension cell(for safety)
+        };
+    }
+
+    // execution mode, argv is `[opcode, spore indices.., cluster_index, mutant pairs..]`
+    debug!("running externally");
+    let index_at = |position: usize| {
+        argv.get(position)
+            .and_then(parse_index)
+            .ok_or(Error::InvalidExtensionOperation)
+    };
+    let opcode = index_at(0)?;
+    let (fixed_len, input_index, output_index) = match opcode {
+        MUTANT_OP_SPORE_MINT => (3, None, Some(index_at(1)?)),
+        MUTANT_OP_SPORE_TRANSFER => (4, Some(index_at(1)?), Some(index_at(2)?)),
+        MUTANT_OP_SPORE_BURN => (3, Some(index_at(1)?), None),
+        // cluster lifecycle is not covered by rules
+        _ => return Err(Error::InvalidExtensionOperation),
+    };
+    if argv.len() < fixed_len {
+        return Err(Error::InvalidExtensionOperation);
+    }
+    let (fixed_argv, mutants_argv) = argv.split_at(fixed_len);
+    let (mutants_argv, next_argv) = split_next_extension(mutants_argv);
+    for rules_index in parse_rules_indices(mutants_argv)? {
+        verify_rules(rules_index, opcode, input_index, output_index)?;
+    }
+
+    // mutants of other extensions are left to the next one
+    if let Some(next_argv) = next_argv {
+        exec_next_extension(fixed_argv, next_argv)?;
+    }
+    Ok(())
+}
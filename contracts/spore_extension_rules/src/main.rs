@@ -0,0 +1,30 @@
+//! Generated by capsule
+//!
+//! `main.rs` is used to define rust lang items and modules.
+//! See `entry.rs` for the `main` function.
+//! See `error.rs` for the `Error` type.
+
+#![no_std]
+#![cfg_attr(not(test), no_main)]
+
+// define modules
+mod entry;
+
+#[cfg(test)]
+extern crate alloc;
+
+#[cfg(not(test))]
+use ckb_std::default_alloc;
+#[cfg(not(test))]
+ckb_std::entry!(program_entry);
+#[cfg(not(test))]
+default_alloc!();
+
+/// program entry
+pub fn program_entry() -> i8 {
+    // Call main function and return error code
+    match entry::main(ckb_std::env::argv()) {
+        Ok(_) => 0,
+        Err(err) => err as i8,
+    }
+}
@@ -19,6 +19,8 @@ pub struct PublishedCodeHash {
     cluster_agent: String,
     #[serde(default)]
     mutant: String,
+    #[serde(default)]
+    mutant_rules: String,
 }
 
 fn hex_to_byte32(hex: &str) -> [u8; 32] {
@@ -93,6 +95,19 @@ impl FrozenVersions {
             })
             .collect()
     }
+
+    pub fn mutant_rules_code_hashes(&self) -> Vec<[u8; 32]> {
+        self.code_hash_list
+            .iter()
+            .filter_map(|v| {
+                if !v.mutant_rules.is_empty() {
+                    Some(hex_to_byte32(&v.mutant_rules))
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
 }
 
 pub fn load_frozen_toml() -> FrozenVersions {
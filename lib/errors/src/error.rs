@@ -31,6 +31,10 @@ pub enum Error {
     InvalidLuaParameters,
     FailedToLoadLuaLib,
     FailedToCreateLuaInstance,
+    InvalidMutantVersion,
+    LuaCompileFailed,
+    LuaEntryPointMissing,
+    LuaForbiddenApi,
 
     // cluster_proxy errors
     InvalidProxyOperation = 30,
@@ -54,6 +58,7 @@ pub enum Error {
     EmptyName,
     InvalidClusterID,
     MutantNotInDeps,
+    InvalidAllowlistRoot,
 
     // spore errors
     BoundaryEncoding = 60,
@@ -71,6 +76,11 @@ pub enum Error {
     ExtensionPaymentNotEnough,
     ClusterRequiresMutantApplied,
     InvalidExtensionPaymentFormat,
+    AllowlistProofMissing,
+    AllowlistVerifyFailed,
+    PublicMintWindowMismatch,
+    PublicMintPaymentNotEnough,
+    MutantVersionMismatch,
 
     // mime errors
     Illformed = 80,
@@ -83,6 +93,35 @@ pub enum Error {
     ContentOutOfRange,
 
     Unknown,
+
+    // mime errors, continued after Unknown to keep the existing codes unchanged
+    MutantArgsNotMatch = 89,
+    MutantVersionNotValid,
+
+    // spore_extension_rules errors
+    InvalidMutantRules = 91,
+    RulesOperationForbidden,
+    RulesRecipientNotAllowed,
+    RulesHoldingPeriodNotMet,
+
+    // mutant data errors
+    InvalidMutantData,
+
+    // mime errors, continued
+    DuplicateParam,
+
+    // multipart errors
+    TooManyMultipartParts,
+
+    // content validation errors
+    InvalidUtf8Content = 100,
+    InvalidJsonContent,
+    InvalidImageContent,
+    UnsupportedContentEncoding,
+    InvalidEncodedContent,
+    InvalidSporeRef,
+    SporeRefNotInDep,
+    InvalidHashRefContent,
 }
 
 impl From<SysError> for Error {
@@ -822,6 +822,278 @@ impl ::core::iter::IntoIterator for Bytes {
     }
 }
 #[derive(Clone)]
+pub struct Byte32Vec(molecule::bytes::Bytes);
+impl ::core::fmt::LowerHex for Byte32Vec {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        use molecule::hex_string;
+        if f.alternate() {
+            write!(f, "0x")?;
+        }
+        write!(f, "{}", hex_string(self.as_slice()))
+    }
+}
+impl ::core::fmt::Debug for Byte32Vec {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        write!(f, "{}({:#x})", Self::NAME, self)
+    }
+}
+impl ::core::fmt::Display for Byte32Vec {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        write!(f, "{} [", Self::NAME)?;
+        for i in 0..self.len() {
+            if i == 0 {
+                write!(f, "{}", self.get_unchecked(i))?;
+            } else {
+                write!(f, ", {}", self.get_unchecked(i))?;
+            }
+        }
+        write!(f, "]")
+    }
+}
+impl ::core::default::Default for Byte32Vec {
+    fn default() -> Self {
+        let v = molecule::bytes::Bytes::from_static(&Self::DEFAULT_VALUE);
+        Byte32Vec::new_unchecked(v)
+    }
+}
+impl Byte32Vec {
+    const DEFAULT_VALUE: [u8; 4] = [0, 0, 0, 0];
+    pub const ITEM_SIZE: usize = 32;
+    pub fn total_size(&self) -> usize {
+        molecule::NUMBER_SIZE + Self::ITEM_SIZE * self.item_count()
+    }
+    pub fn item_count(&self) -> usize {
+        molecule::unpack_number(self.as_slice()) as usize
+    }
+    pub fn len(&self) -> usize {
+        self.item_count()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    pub fn get(&self, idx: usize) -> Option<Byte32> {
+        if idx >= self.len() {
+            None
+        } else {
+            Some(self.get_unchecked(idx))
+        }
+    }
+    pub fn get_unchecked(&self, idx: usize) -> Byte32 {
+        let start = molecule::NUMBER_SIZE + Self::ITEM_SIZE * idx;
+        let end = start + Self::ITEM_SIZE;
+        Byte32::new_unchecked(self.0.slice(start..end))
+    }
+    pub fn as_reader<'r>(&'r self) -> Byte32VecReader<'r> {
+        Byte32VecReader::new_unchecked(self.as_slice())
+    }
+}
+impl molecule::prelude::Entity for Byte32Vec {
+    type Builder = Byte32VecBuilder;
+    const NAME: &'static str = "Byte32Vec";
+    fn new_unchecked(data: molecule::bytes::Bytes) -> Self {
+        Byte32Vec(data)
+    }
+    fn as_bytes(&self) -> molecule::bytes::Bytes {
+        self.0.clone()
+    }
+    fn as_slice(&self) -> &[u8] {
+        &self.0[..]
+    }
+    fn from_slice(slice: &[u8]) -> molecule::error::VerificationResult<Self> {
+        Byte32VecReader::from_slice(slice).map(|reader| reader.to_entity())
+    }
+    fn from_compatible_slice(slice: &[u8]) -> molecule::error::VerificationResult<Self> {
+        Byte32VecReader::from_compatible_slice(slice).map(|reader| reader.to_entity())
+    }
+    fn new_builder() -> Self::Builder {
+        ::core::default::Default::default()
+    }
+    fn as_builder(self) -> Self::Builder {
+        Self::new_builder().extend(self.into_iter())
+    }
+}
+#[derive(Clone, Copy)]
+pub struct Byte32VecReader<'r>(&'r [u8]);
+impl<'r> ::core::fmt::LowerHex for Byte32VecReader<'r> {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        use molecule::hex_string;
+        if f.alternate() {
+            write!(f, "0x")?;
+        }
+        write!(f, "{}", hex_string(self.as_slice()))
+    }
+}
+impl<'r> ::core::fmt::Debug for Byte32VecReader<'r> {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        write!(f, "{}({:#x})", Self::NAME, self)
+    }
+}
+impl<'r> ::core::fmt::Display for Byte32VecReader<'r> {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        write!(f, "{} [", Self::NAME)?;
+        for i in 0..self.len() {
+            if i == 0 {
+                write!(f, "{}", self.get_unchecked(i))?;
+            } else {
+                write!(f, ", {}", self.get_unchecked(i))?;
+            }
+        }
+        write!(f, "]")
+    }
+}
+impl<'r> Byte32VecReader<'r> {
+    pub const ITEM_SIZE: usize = 32;
+    pub fn total_size(&self) -> usize {
+        molecule::NUMBER_SIZE + Self::ITEM_SIZE * self.item_count()
+    }
+    pub fn item_count(&self) -> usize {
+        molecule::unpack_number(self.as_slice()) as usize
+    }
+    pub fn len(&self) -> usize {
+        self.item_count()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    pub fn get(&self, idx: usize) -> Option<Byte32Reader<'r>> {
+        if idx >= self.len() {
+            None
+        } else {
+            Some(self.get_unchecked(idx))
+        }
+    }
+    pub fn get_unchecked(&self, idx: usize) -> Byte32Reader<'r> {
+        let start = molecule::NUMBER_SIZE + Self::ITEM_SIZE * idx;
+        let end = start + Self::ITEM_SIZE;
+        Byte32Reader::new_unchecked(&self.as_slice()[start..end])
+    }
+}
+impl<'r> molecule::prelude::Reader<'r> for Byte32VecReader<'r> {
+    type Entity = Byte32Vec;
+    const NAME: &'static str = "Byte32VecReader";
+    fn to_entity(&self) -> Self::Entity {
+        Self::Entity::new_unchecked(self.as_slice().to_owned().into())
+    }
+    fn new_unchecked(slice: &'r [u8]) -> Self {
+        Byte32VecReader(slice)
+    }
+    fn as_slice(&self) -> &'r [u8] {
+        self.0
+    }
+    fn verify(slice: &[u8], _compatible: bool) -> molecule::error::VerificationResult<()> {
+        use molecule::verification_error as ve;
+        let slice_len = slice.len();
+        if slice_len < molecule::NUMBER_SIZE {
+            return ve!(Self, HeaderIsBroken, molecule::NUMBER_SIZE, slice_len);
+        }
+        let item_count = molecule::unpack_number(slice) as usize;
+        if item_count == 0 {
+            if slice_len != molecule::NUMBER_SIZE {
+                return ve!(Self, TotalSizeNotMatch, molecule::NUMBER_SIZE, slice_len);
+            }
+            return Ok(());
+        }
+        let total_size = molecule::NUMBER_SIZE + Self::ITEM_SIZE * item_count;
+        if slice_len != total_size {
+            return ve!(Self, TotalSizeNotMatch, total_size, slice_len);
+        }
+        Ok(())
+    }
+}
+#[derive(Clone, Debug, Default)]
+pub struct Byte32VecBuilder(pub(crate) Vec<Byte32>);
+impl Byte32VecBuilder {
+    pub const ITEM_SIZE: usize = 32;
+    pub fn set(mut self, v: Vec<Byte32>) -> Self {
+        self.0 = v;
+        self
+    }
+    pub fn push(mut self, v: Byte32) -> Self {
+        self.0.push(v);
+        self
+    }
+    pub fn extend<T: ::core::iter::IntoIterator<Item = Byte32>>(mut self, iter: T) -> Self {
+        for elem in iter {
+            self.0.push(elem);
+        }
+        self
+    }
+    pub fn replace(&mut self, index: usize, v: Byte32) -> Option<Byte32> {
+        self.0
+            .get_mut(index)
+            .map(|item| ::core::mem::replace(item, v))
+    }
+}
+impl molecule::prelude::Builder for Byte32VecBuilder {
+    type Entity = Byte32Vec;
+    const NAME: &'static str = "Byte32VecBuilder";
+    fn expected_length(&self) -> usize {
+        molecule::NUMBER_SIZE + Self::ITEM_SIZE * self.0.len()
+    }
+    fn write<W: molecule::io::Write>(&self, writer: &mut W) -> molecule::io::Result<()> {
+        writer.write_all(&molecule::pack_number(self.0.len() as molecule::Number))?;
+        for inner in &self.0[..] {
+            writer.write_all(inner.as_slice())?;
+        }
+        Ok(())
+    }
+    fn build(&self) -> Self::Entity {
+        let mut inner = Vec::with_capacity(self.expected_length());
+        self.write(&mut inner)
+            .unwrap_or_else(|_| panic!("{} build should be ok", Self::NAME));
+        Byte32Vec::new_unchecked(inner.into())
+    }
+}
+pub struct Byte32VecIterator(Byte32Vec, usize, usize);
+impl ::core::iter::Iterator for Byte32VecIterator {
+    type Item = Byte32;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.1 >= self.2 {
+            None
+        } else {
+            let ret = self.0.get_unchecked(self.1);
+            self.1 += 1;
+            Some(ret)
+        }
+    }
+}
+impl ::core::iter::ExactSizeIterator for Byte32VecIterator {
+    fn len(&self) -> usize {
+        self.2 - self.1
+    }
+}
+impl ::core::iter::IntoIterator for Byte32Vec {
+    type Item = Byte32;
+    type IntoIter = Byte32VecIterator;
+    fn into_iter(self) -> Self::IntoIter {
+        let len = self.len();
+        Byte32VecIterator(self, 0, len)
+    }
+}
+impl<'r> Byte32VecReader<'r> {
+    pub fn iter<'t>(&'t self) -> Byte32VecReaderIterator<'t, 'r> {
+        Byte32VecReaderIterator(&self, 0, self.len())
+    }
+}
+pub struct Byte32VecReaderIterator<'t, 'r>(&'t Byte32VecReader<'r>, usize, usize);
+impl<'t: 'r, 'r> ::core::iter::Iterator for Byte32VecReaderIterator<'t, 'r> {
+    type Item = Byte32Reader<'t>;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.1 >= self.2 {
+            None
+        } else {
+            let ret = self.0.get_unchecked(self.1);
+            self.1 += 1;
+            Some(ret)
+        }
+    }
+}
+impl<'t: 'r, 'r> ::core::iter::ExactSizeIterator for Byte32VecReaderIterator<'t, 'r> {
+    fn len(&self) -> usize {
+        self.2 - self.1
+    }
+}
+#[derive(Clone)]
 pub struct Script(molecule::bytes::Bytes);
 impl ::core::fmt::LowerHex for Script {
     fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
@@ -1668,6 +1940,322 @@ impl molecule::prelude::Builder for MintSporeBuilder {
     }
 }
 #[derive(Clone)]
+pub struct MintSporeWithProof(molecule::bytes::Bytes);
+impl ::core::fmt::LowerHex for MintSporeWithProof {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        use molecule::hex_string;
+        if f.alternate() {
+            write!(f, "0x")?;
+        }
+        write!(f, "{}", hex_string(self.as_slice()))
+    }
+}
+impl ::core::fmt::Debug for MintSporeWithProof {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        write!(f, "{}({:#x})", Self::NAME, self)
+    }
+}
+impl ::core::fmt::Display for MintSporeWithProof {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        write!(f, "{} {{ ", Self::NAME)?;
+        write!(f, "{}: {}", "spore_id", self.spore_id())?;
+        write!(f, ", {}: {}", "to", self.to())?;
+        write!(f, ", {}: {}", "data_hash", self.data_hash())?;
+        write!(f, ", {}: {}", "allowlist_proof", self.allowlist_proof())?;
+        let extra_count = self.count_extra_fields();
+        if extra_count != 0 {
+            write!(f, ", .. ({} fields)", extra_count)?;
+        }
+        write!(f, " }}")
+    }
+}
+impl ::core::default::Default for MintSporeWithProof {
+    fn default() -> Self {
+        let v = molecule::bytes::Bytes::from_static(&Self::DEFAULT_VALUE);
+        MintSporeWithProof::new_unchecked(v)
+    }
+}
+impl MintSporeWithProof {
+    const DEFAULT_VALUE: [u8; 145] = [
+        145, 0, 0, 0, 20, 0, 0, 0, 52, 0, 0, 0, 109, 0, 0, 0, 141, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 53, 0,
+        0, 0, 16, 0, 0, 0, 48, 0, 0, 0, 49, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    ];
+    pub const FIELD_COUNT: usize = 4;
+    pub fn total_size(&self) -> usize {
+        molecule::unpack_number(self.as_slice()) as usize
+    }
+    pub fn field_count(&self) -> usize {
+        if self.total_size() == molecule::NUMBER_SIZE {
+            0
+        } else {
+            (molecule::unpack_number(&self.as_slice()[molecule::NUMBER_SIZE..]) as usize / 4) - 1
+        }
+    }
+    pub fn count_extra_fields(&self) -> usize {
+        self.field_count() - Self::FIELD_COUNT
+    }
+    pub fn has_extra_fields(&self) -> bool {
+        Self::FIELD_COUNT != self.field_count()
+    }
+    pub fn spore_id(&self) -> Byte32 {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[4..]) as usize;
+        let end = molecule::unpack_number(&slice[8..]) as usize;
+        Byte32::new_unchecked(self.0.slice(start..end))
+    }
+    pub fn to(&self) -> Address {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[8..]) as usize;
+        let end = molecule::unpack_number(&slice[12..]) as usize;
+        Address::new_unchecked(self.0.slice(start..end))
+    }
+    pub fn data_hash(&self) -> Byte32 {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[12..]) as usize;
+        let end = molecule::unpack_number(&slice[16..]) as usize;
+        Byte32::new_unchecked(self.0.slice(start..end))
+    }
+    pub fn allowlist_proof(&self) -> Byte32Vec {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[16..]) as usize;
+        if self.has_extra_fields() {
+            let end = molecule::unpack_number(&slice[20..]) as usize;
+            Byte32Vec::new_unchecked(self.0.slice(start..end))
+        } else {
+            Byte32Vec::new_unchecked(self.0.slice(start..))
+        }
+    }
+    pub fn as_reader<'r>(&'r self) -> MintSporeWithProofReader<'r> {
+        MintSporeWithProofReader::new_unchecked(self.as_slice())
+    }
+}
+impl molecule::prelude::Entity for MintSporeWithProof {
+    type Builder = MintSporeWithProofBuilder;
+    const NAME: &'static str = "MintSporeWithProof";
+    fn new_unchecked(data: molecule::bytes::Bytes) -> Self {
+        MintSporeWithProof(data)
+    }
+    fn as_bytes(&self) -> molecule::bytes::Bytes {
+        self.0.clone()
+    }
+    fn as_slice(&self) -> &[u8] {
+        &self.0[..]
+    }
+    fn from_slice(slice: &[u8]) -> molecule::error::VerificationResult<Self> {
+        MintSporeWithProofReader::from_slice(slice).map(|reader| reader.to_entity())
+    }
+    fn from_compatible_slice(slice: &[u8]) -> molecule::error::VerificationResult<Self> {
+        MintSporeWithProofReader::from_compatible_slice(slice).map(|reader| reader.to_entity())
+    }
+    fn new_builder() -> Self::Builder {
+        ::core::default::Default::default()
+    }
+    fn as_builder(self) -> Self::Builder {
+        Self::new_builder()
+            .spore_id(self.spore_id())
+            .to(self.to())
+            .data_hash(self.data_hash())
+            .allowlist_proof(self.allowlist_proof())
+    }
+}
+#[derive(Clone, Copy)]
+pub struct MintSporeWithProofReader<'r>(&'r [u8]);
+impl<'r> ::core::fmt::LowerHex for MintSporeWithProofReader<'r> {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        use molecule::hex_string;
+        if f.alternate() {
+            write!(f, "0x")?;
+        }
+        write!(f, "{}", hex_string(self.as_slice()))
+    }
+}
+impl<'r> ::core::fmt::Debug for MintSporeWithProofReader<'r> {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        write!(f, "{}({:#x})", Self::NAME, self)
+    }
+}
+impl<'r> ::core::fmt::Display for MintSporeWithProofReader<'r> {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        write!(f, "{} {{ ", Self::NAME)?;
+        write!(f, "{}: {}", "spore_id", self.spore_id())?;
+        write!(f, ", {}: {}", "to", self.to())?;
+        write!(f, ", {}: {}", "data_hash", self.data_hash())?;
+        write!(f, ", {}: {}", "allowlist_proof", self.allowlist_proof())?;
+        let extra_count = self.count_extra_fields();
+        if extra_count != 0 {
+            write!(f, ", .. ({} fields)", extra_count)?;
+        }
+        write!(f, " }}")
+    }
+}
+impl<'r> MintSporeWithProofReader<'r> {
+    pub const FIELD_COUNT: usize = 4;
+    pub fn total_size(&self) -> usize {
+        molecule::unpack_number(self.as_slice()) as usize
+    }
+    pub fn field_count(&self) -> usize {
+        if self.total_size() == molecule::NUMBER_SIZE {
+            0
+        } else {
+            (molecule::unpack_number(&self.as_slice()[molecule::NUMBER_SIZE..]) as usize / 4) - 1
+        }
+    }
+    pub fn count_extra_fields(&self) -> usize {
+        self.field_count() - Self::FIELD_COUNT
+    }
+    pub fn has_extra_fields(&self) -> bool {
+        Self::FIELD_COUNT != self.field_count()
+    }
+    pub fn spore_id(&self) -> Byte32Reader<'r> {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[4..]) as usize;
+        let end = molecule::unpack_number(&slice[8..]) as usize;
+        Byte32Reader::new_unchecked(&self.as_slice()[start..end])
+    }
+    pub fn to(&self) -> AddressReader<'r> {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[8..]) as usize;
+        let end = molecule::unpack_number(&slice[12..]) as usize;
+        AddressReader::new_unchecked(&self.as_slice()[start..end])
+    }
+    pub fn data_hash(&self) -> Byte32Reader<'r> {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[12..]) as usize;
+        let end = molecule::unpack_number(&slice[16..]) as usize;
+        Byte32Reader::new_unchecked(&self.as_slice()[start..end])
+    }
+    pub fn allowlist_proof(&self) -> Byte32VecReader<'r> {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[16..]) as usize;
+        if self.has_extra_fields() {
+            let end = molecule::unpack_number(&slice[20..]) as usize;
+            Byte32VecReader::new_unchecked(&self.as_slice()[start..end])
+        } else {
+            Byte32VecReader::new_unchecked(&self.as_slice()[start..])
+        }
+    }
+}
+impl<'r> molecule::prelude::Reader<'r> for MintSporeWithProofReader<'r> {
+    type Entity = MintSporeWithProof;
+    const NAME: &'static str = "MintSporeWithProofReader";
+    fn to_entity(&self) -> Self::Entity {
+        Self::Entity::new_unchecked(self.as_slice().to_owned().into())
+    }
+    fn new_unchecked(slice: &'r [u8]) -> Self {
+        MintSporeWithProofReader(slice)
+    }
+    fn as_slice(&self) -> &'r [u8] {
+        self.0
+    }
+    fn verify(slice: &[u8], compatible: bool) -> molecule::error::VerificationResult<()> {
+        use molecule::verification_error as ve;
+        let slice_len = slice.len();
+        if slice_len < molecule::NUMBER_SIZE {
+            return ve!(Self, HeaderIsBroken, molecule::NUMBER_SIZE, slice_len);
+        }
+        let total_size = molecule::unpack_number(slice) as usize;
+        if slice_len != total_size {
+            return ve!(Self, TotalSizeNotMatch, total_size, slice_len);
+        }
+        if slice_len < molecule::NUMBER_SIZE * 2 {
+            return ve!(Self, HeaderIsBroken, molecule::NUMBER_SIZE * 2, slice_len);
+        }
+        let offset_first = molecule::unpack_number(&slice[molecule::NUMBER_SIZE..]) as usize;
+        if offset_first % molecule::NUMBER_SIZE != 0 || offset_first < molecule::NUMBER_SIZE * 2 {
+            return ve!(Self, OffsetsNotMatch);
+        }
+        if slice_len < offset_first {
+            return ve!(Self, HeaderIsBroken, offset_first, slice_len);
+        }
+        let field_count = offset_first / molecule::NUMBER_SIZE - 1;
+        if field_count < Self::FIELD_COUNT {
+            return ve!(Self, FieldCountNotMatch, Self::FIELD_COUNT, field_count);
+        } else if !compatible && field_count > Self::FIELD_COUNT {
+            return ve!(Self, FieldCountNotMatch, Self::FIELD_COUNT, field_count);
+        };
+        let mut offsets: Vec<usize> = slice[molecule::NUMBER_SIZE..offset_first]
+            .chunks_exact(molecule::NUMBER_SIZE)
+            .map(|x| molecule::unpack_number(x) as usize)
+            .collect();
+        offsets.push(total_size);
+        if offsets.windows(2).any(|i| i[0] > i[1]) {
+            return ve!(Self, OffsetsNotMatch);
+        }
+        Byte32Reader::verify(&slice[offsets[0]..offsets[1]], compatible)?;
+        AddressReader::verify(&slice[offsets[1]..offsets[2]], compatible)?;
+        Byte32Reader::verify(&slice[offsets[2]..offsets[3]], compatible)?;
+        Byte32VecReader::verify(&slice[offsets[3]..offsets[4]], compatible)?;
+        Ok(())
+    }
+}
+#[derive(Clone, Debug, Default)]
+pub struct MintSporeWithProofBuilder {
+    pub(crate) spore_id: Byte32,
+    pub(crate) to: Address,
+    pub(crate) data_hash: Byte32,
+    pub(crate) allowlist_proof: Byte32Vec,
+}
+impl MintSporeWithProofBuilder {
+    pub const FIELD_COUNT: usize = 4;
+    pub fn spore_id(mut self, v: Byte32) -> Self {
+        self.spore_id = v;
+        self
+    }
+    pub fn to(mut self, v: Address) -> Self {
+        self.to = v;
+        self
+    }
+    pub fn data_hash(mut self, v: Byte32) -> Self {
+        self.data_hash = v;
+        self
+    }
+    pub fn allowlist_proof(mut self, v: Byte32Vec) -> Self {
+        self.allowlist_proof = v;
+        self
+    }
+}
+impl molecule::prelude::Builder for MintSporeWithProofBuilder {
+    type Entity = MintSporeWithProof;
+    const NAME: &'static str = "MintSporeWithProofBuilder";
+    fn expected_length(&self) -> usize {
+        molecule::NUMBER_SIZE * (Self::FIELD_COUNT + 1)
+            + self.spore_id.as_slice().len()
+            + self.to.as_slice().len()
+            + self.data_hash.as_slice().len()
+            + self.allowlist_proof.as_slice().len()
+    }
+    fn write<W: molecule::io::Write>(&self, writer: &mut W) -> molecule::io::Result<()> {
+        let mut total_size = molecule::NUMBER_SIZE * (Self::FIELD_COUNT + 1);
+        let mut offsets = Vec::with_capacity(Self::FIELD_COUNT);
+        offsets.push(total_size);
+        total_size += self.spore_id.as_slice().len();
+        offsets.push(total_size);
+        total_size += self.to.as_slice().len();
+        offsets.push(total_size);
+        total_size += self.data_hash.as_slice().len();
+        offsets.push(total_size);
+        total_size += self.allowlist_proof.as_slice().len();
+        writer.write_all(&molecule::pack_number(total_size as molecule::Number))?;
+        for offset in offsets.into_iter() {
+            writer.write_all(&molecule::pack_number(offset as molecule::Number))?;
+        }
+        writer.write_all(self.spore_id.as_slice())?;
+        writer.write_all(self.to.as_slice())?;
+        writer.write_all(self.data_hash.as_slice())?;
+        writer.write_all(self.allowlist_proof.as_slice())?;
+        Ok(())
+    }
+    fn build(&self) -> Self::Entity {
+        let mut inner = Vec::with_capacity(self.expected_length());
+        self.write(&mut inner)
+            .unwrap_or_else(|_| panic!("{} build should be ok", Self::NAME));
+        MintSporeWithProof::new_unchecked(inner.into())
+    }
+}
+#[derive(Clone)]
 pub struct TransferSpore(molecule::bytes::Bytes);
 impl ::core::fmt::LowerHex for TransferSpore {
     fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
@@ -4592,7 +5180,7 @@ impl SporeAction {
         0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
         0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
     ];
-    pub const ITEMS_COUNT: usize = 11;
+    pub const ITEMS_COUNT: usize = 12;
     pub fn item_id(&self) -> molecule::Number {
         molecule::unpack_number(self.as_slice())
     }
@@ -4610,6 +5198,7 @@ impl SporeAction {
             8 => MintAgent::new_unchecked(inner).into(),
             9 => TransferAgent::new_unchecked(inner).into(),
             10 => BurnAgent::new_unchecked(inner).into(),
+            11 => MintSporeWithProof::new_unchecked(inner).into(),
             _ => panic!("{}: invalid data", Self::NAME),
         }
     }
@@ -4666,7 +5255,7 @@ impl<'r> ::core::fmt::Display for SporeActionReader<'r> {
     }
 }
 impl<'r> SporeActionReader<'r> {
-    pub const ITEMS_COUNT: usize = 11;
+    pub const ITEMS_COUNT: usize = 12;
     pub fn item_id(&self) -> molecule::Number {
         molecule::unpack_number(self.as_slice())
     }
@@ -4684,6 +5273,7 @@ impl<'r> SporeActionReader<'r> {
             8 => MintAgentReader::new_unchecked(inner).into(),
             9 => TransferAgentReader::new_unchecked(inner).into(),
             10 => BurnAgentReader::new_unchecked(inner).into(),
+            11 => MintSporeWithProofReader::new_unchecked(inner).into(),
             _ => panic!("{}: invalid data", Self::NAME),
         }
     }
@@ -4720,6 +5310,7 @@ impl<'r> molecule::prelude::Reader<'r> for SporeActionReader<'r> {
             8 => MintAgentReader::verify(inner_slice, compatible),
             9 => TransferAgentReader::verify(inner_slice, compatible),
             10 => BurnAgentReader::verify(inner_slice, compatible),
+            11 => MintSporeWithProofReader::verify(inner_slice, compatible),
             _ => ve!(Self, UnknownItem, Self::ITEMS_COUNT, item_id),
         }?;
         Ok(())
@@ -4728,7 +5319,7 @@ impl<'r> molecule::prelude::Reader<'r> for SporeActionReader<'r> {
 #[derive(Clone, Debug, Default)]
 pub struct SporeActionBuilder(pub(crate) SporeActionUnion);
 impl SporeActionBuilder {
-    pub const ITEMS_COUNT: usize = 11;
+    pub const ITEMS_COUNT: usize = 12;
     pub fn set<I>(mut self, v: I) -> Self
     where
         I: ::core::convert::Into<SporeActionUnion>,
@@ -4767,6 +5358,7 @@ pub enum SporeActionUnion {
     MintAgent(MintAgent),
     TransferAgent(TransferAgent),
     BurnAgent(BurnAgent),
+    MintSporeWithProof(MintSporeWithProof),
 }
 #[derive(Debug, Clone, Copy)]
 pub enum SporeActionUnionReader<'r> {
@@ -4781,6 +5373,7 @@ pub enum SporeActionUnionReader<'r> {
     MintAgent(MintAgentReader<'r>),
     TransferAgent(TransferAgentReader<'r>),
     BurnAgent(BurnAgentReader<'r>),
+    MintSporeWithProof(MintSporeWithProofReader<'r>),
 }
 impl ::core::default::Default for SporeActionUnion {
     fn default() -> Self {
@@ -4823,6 +5416,9 @@ impl ::core::fmt::Display for SporeActionUnion {
             SporeActionUnion::BurnAgent(ref item) => {
                 write!(f, "{}::{}({})", Self::NAME, BurnAgent::NAME, item)
             }
+            SporeActionUnion::MintSporeWithProof(ref item) => {
+                write!(f, "{}::{}({})", Self::NAME, MintSporeWithProof::NAME, item)
+            }
         }
     }
 }
@@ -4862,6 +5458,9 @@ impl<'r> ::core::fmt::Display for SporeActionUnionReader<'r> {
             SporeActionUnionReader::BurnAgent(ref item) => {
                 write!(f, "{}::{}({})", Self::NAME, BurnAgent::NAME, item)
             }
+            SporeActionUnionReader::MintSporeWithProof(ref item) => {
+                write!(f, "{}::{}({})", Self::NAME, MintSporeWithProof::NAME, item)
+            }
         }
     }
 }
@@ -4879,6 +5478,7 @@ impl SporeActionUnion {
             SporeActionUnion::MintAgent(ref item) => write!(f, "{}", item),
             SporeActionUnion::TransferAgent(ref item) => write!(f, "{}", item),
             SporeActionUnion::BurnAgent(ref item) => write!(f, "{}", item),
+            SporeActionUnion::MintSporeWithProof(ref item) => write!(f, "{}", item),
         }
     }
 }
@@ -4896,6 +5496,7 @@ impl<'r> SporeActionUnionReader<'r> {
             SporeActionUnionReader::MintAgent(ref item) => write!(f, "{}", item),
             SporeActionUnionReader::TransferAgent(ref item) => write!(f, "{}", item),
             SporeActionUnionReader::BurnAgent(ref item) => write!(f, "{}", item),
+            SporeActionUnionReader::MintSporeWithProof(ref item) => write!(f, "{}", item),
         }
     }
 }
@@ -4954,6 +5555,11 @@ impl ::core::convert::From<BurnAgent> for SporeActionUnion {
         SporeActionUnion::BurnAgent(item)
     }
 }
+impl ::core::convert::From<MintSporeWithProof> for SporeActionUnion {
+    fn from(item: MintSporeWithProof) -> Self {
+        SporeActionUnion::MintSporeWithProof(item)
+    }
+}
 impl<'r> ::core::convert::From<MintSporeReader<'r>> for SporeActionUnionReader<'r> {
     fn from(item: MintSporeReader<'r>) -> Self {
         SporeActionUnionReader::MintSpore(item)
@@ -5009,6 +5615,11 @@ impl<'r> ::core::convert::From<BurnAgentReader<'r>> for SporeActionUnionReader<'
         SporeActionUnionReader::BurnAgent(item)
     }
 }
+impl<'r> ::core::convert::From<MintSporeWithProofReader<'r>> for SporeActionUnionReader<'r> {
+    fn from(item: MintSporeWithProofReader<'r>) -> Self {
+        SporeActionUnionReader::MintSporeWithProof(item)
+    }
+}
 impl SporeActionUnion {
     pub const NAME: &'static str = "SporeActionUnion";
     pub fn as_bytes(&self) -> molecule::bytes::Bytes {
@@ -5024,6 +5635,7 @@ impl SporeActionUnion {
             SporeActionUnion::MintAgent(item) => item.as_bytes(),
             SporeActionUnion::TransferAgent(item) => item.as_bytes(),
             SporeActionUnion::BurnAgent(item) => item.as_bytes(),
+            SporeActionUnion::MintSporeWithProof(item) => item.as_bytes(),
         }
     }
     pub fn as_slice(&self) -> &[u8] {
@@ -5039,6 +5651,7 @@ impl SporeActionUnion {
             SporeActionUnion::MintAgent(item) => item.as_slice(),
             SporeActionUnion::TransferAgent(item) => item.as_slice(),
             SporeActionUnion::BurnAgent(item) => item.as_slice(),
+            SporeActionUnion::MintSporeWithProof(item) => item.as_slice(),
         }
     }
     pub fn item_id(&self) -> molecule::Number {
@@ -5054,6 +5667,7 @@ impl SporeActionUnion {
             SporeActionUnion::MintAgent(_) => 8,
             SporeActionUnion::TransferAgent(_) => 9,
             SporeActionUnion::BurnAgent(_) => 10,
+            SporeActionUnion::MintSporeWithProof(_) => 11,
         }
     }
     pub fn item_name(&self) -> &str {
@@ -5069,6 +5683,7 @@ impl SporeActionUnion {
             SporeActionUnion::MintAgent(_) => "MintAgent",
             SporeActionUnion::TransferAgent(_) => "TransferAgent",
             SporeActionUnion::BurnAgent(_) => "BurnAgent",
+            SporeActionUnion::MintSporeWithProof(_) => "MintSporeWithProof",
         }
     }
     pub fn as_reader<'r>(&'r self) -> SporeActionUnionReader<'r> {
@@ -5084,6 +5699,7 @@ impl SporeActionUnion {
             SporeActionUnion::MintAgent(item) => item.as_reader().into(),
             SporeActionUnion::TransferAgent(item) => item.as_reader().into(),
             SporeActionUnion::BurnAgent(item) => item.as_reader().into(),
+            SporeActionUnion::MintSporeWithProof(item) => item.as_reader().into(),
         }
     }
 }
@@ -5102,6 +5718,7 @@ impl<'r> SporeActionUnionReader<'r> {
             SporeActionUnionReader::MintAgent(item) => item.as_slice(),
             SporeActionUnionReader::TransferAgent(item) => item.as_slice(),
             SporeActionUnionReader::BurnAgent(item) => item.as_slice(),
+            SporeActionUnionReader::MintSporeWithProof(item) => item.as_slice(),
         }
     }
     pub fn item_id(&self) -> molecule::Number {
@@ -5117,6 +5734,7 @@ impl<'r> SporeActionUnionReader<'r> {
             SporeActionUnionReader::MintAgent(_) => 8,
             SporeActionUnionReader::TransferAgent(_) => 9,
             SporeActionUnionReader::BurnAgent(_) => 10,
+            SporeActionUnionReader::MintSporeWithProof(_) => 11,
         }
     }
     pub fn item_name(&self) -> &str {
@@ -5132,6 +5750,7 @@ impl<'r> SporeActionUnionReader<'r> {
             SporeActionUnionReader::MintAgent(_) => "MintAgent",
             SporeActionUnionReader::TransferAgent(_) => "TransferAgent",
             SporeActionUnionReader::BurnAgent(_) => "BurnAgent",
+            SporeActionUnionReader::MintSporeWithProof(_) => "MintSporeWithProof",
         }
     }
 }
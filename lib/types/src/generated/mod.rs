@@ -1,9 +1,14 @@
 mod spore_v1;
 mod spore_v2;
+mod spore_v3;
 
 pub mod spore {
     pub use super::spore_v1::*;
     pub use super::spore_v2::*;
+    pub use super::spore_v3::*;
 }
 
 pub mod action;
+pub mod hashref;
+pub mod mutant_data;
+pub mod mutant_rules;
@@ -0,0 +1,205 @@
+// Content hash commitment of spore, i.e. content of `application/vnd.spore.hashref`, which keeps
+// large content off-chain and commits to its bytes by hash and size
+//
+// note: contracts only check the structure of commitment, bytes are verified off-chain by fetching
+// them from one of the URIs
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use molecule::prelude::{Builder, Byte, Entity, Reader};
+
+use crate::generated::hashref::{
+    Byte32, Bytes, BytesVec, ContentHashRef, ContentHashRefReader, Uint64,
+};
+
+/// Content type of spores whose content is a molecule `ContentHashRef`
+pub const HASHREF_CONTENT_TYPE: &str = "application/vnd.spore.hashref";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum HashAlgorithm {
+    /// blake2b-256 with CKB personalization `ckb-default-hash`
+    Blake2b256 = 0,
+    Sha256 = 1,
+}
+
+impl HashAlgorithm {
+    pub fn from_byte(byte: u8) -> Option<Self> {
+        match byte {
+            0 => Some(Self::Blake2b256),
+            1 => Some(Self::Sha256),
+            _ => None,
+        }
+    }
+
+    /// Hashes content off-chain, e.g. to build or verify a commitment
+    #[cfg(feature = "std")]
+    pub fn hash(&self, content: &[u8]) -> [u8; 32] {
+        let mut hash = [0u8; 32];
+        match self {
+            Self::Blake2b256 => {
+                let mut blake2b = blake2b_ref::Blake2bBuilder::new(32)
+                    .personal(b"ckb-default-hash")
+                    .build();
+                blake2b.update(content);
+                blake2b.finalize(&mut hash);
+            }
+            Self::Sha256 => {
+                use sha2::Digest;
+                hash.copy_from_slice(&sha2::Sha256::digest(content));
+            }
+        }
+        hash
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NativeContentHashRef {
+    pub algorithm: HashAlgorithm,
+    pub hash: [u8; 32],
+    pub size: u64,
+    /// locations of content in order of preference, e.g. `ipfs://<cid>` or `file://<path>`
+    pub uris: Vec<String>,
+}
+
+impl NativeContentHashRef {
+    /// Commits to content by hashing it off-chain
+    #[cfg(feature = "std")]
+    pub fn new(algorithm: HashAlgorithm, content: &[u8], uris: Vec<String>) -> Self {
+        Self {
+            algorithm,
+            hash: algorithm.hash(content),
+            size: content.len() as u64,
+            uris,
+        }
+    }
+
+    /// Parses a commitment with the same rules as contracts, i.e. a known algorithm, non-zero size
+    /// and at least one non-empty URI in UTF-8, and extra fields are allowed
+    pub fn from_slice(slice: &[u8]) -> Option<Self> {
+        let hashref = ContentHashRefReader::from_compatible_slice(slice).ok()?;
+        let algorithm = HashAlgorithm::from_byte(hashref.algorithm().as_slice()[0])?;
+        let size = u64::from_le_bytes(hashref.size().as_slice().try_into().ok()?);
+        let uris = hashref
+            .uris()
+            .iter()
+            .map(|uri| {
+                let uri = core::str::from_utf8(uri.raw_data()).ok()?;
+                (!uri.is_empty()).then(|| String::from(uri))
+            })
+            .collect::<Option<Vec<_>>>()?;
+        if size == 0 || uris.is_empty() {
+            return None;
+        }
+        Some(Self {
+            algorithm,
+            hash: hashref.hash().as_slice().try_into().ok()?,
+            size,
+            uris,
+        })
+    }
+
+    /// Checks if content matches the commitment by size and hash
+    #[cfg(feature = "std")]
+    pub fn matches(&self, content: &[u8]) -> bool {
+        content.len() as u64 == self.size && self.algorithm.hash(content) == self.hash
+    }
+
+    /// Fetches content from URIs in order and returns the first one matching the commitment
+    #[cfg(feature = "std")]
+    pub fn verify<S: ContentStore + ?Sized>(&self, store: &S) -> Result<Vec<u8>, HashRefError> {
+        let mut error = HashRefError::ContentNotFound;
+        for uri in &self.uris {
+            let Some(content) = store.fetch(uri) else {
+                continue;
+            };
+            if self.matches(&content) {
+                return Ok(content);
+            }
+            error = HashRefError::ContentMismatch;
+        }
+        Err(error)
+    }
+}
+
+impl From<NativeContentHashRef> for ContentHashRef {
+    fn from(hashref: NativeContentHashRef) -> Self {
+        let uris = hashref.uris.iter().map(|uri| {
+            Bytes::new_builder()
+                .extend(uri.bytes().map(Byte::new))
+                .build()
+        });
+        ContentHashRef::new_builder()
+            .algorithm(Byte::new(hashref.algorithm as u8))
+            .hash(Byte32::new_unchecked(hashref.hash.to_vec().into()))
+            .size(Uint64::new_unchecked(
+                hashref.size.to_le_bytes().to_vec().into(),
+            ))
+            .uris(BytesVec::new_builder().extend(uris).build())
+            .build()
+    }
+}
+
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HashRefError {
+    /// content is unavailable from all of the URIs
+    ContentNotFound,
+    /// content is fetched but its size or hash mismatches the commitment
+    ContentMismatch,
+}
+
+/// Source of off-chain content referred by URIs of commitment
+#[cfg(feature = "std")]
+pub trait ContentStore {
+    /// Returns none if the URI is unsupported or the content is unavailable
+    fn fetch(&self, uri: &str) -> Option<Vec<u8>>;
+}
+
+/// In-memory store keyed by URI
+#[cfg(feature = "std")]
+impl ContentStore for std::collections::HashMap<String, Vec<u8>> {
+    fn fetch(&self, uri: &str) -> Option<Vec<u8>> {
+        self.get(uri).cloned()
+    }
+}
+
+/// Store of local files, which serves `file://<absolute_path>` and relative paths under its root,
+/// other schemes are unsupported, and so are relative paths escaping the root, i.e. absolute ones
+/// or the ones with `..`
+#[cfg(feature = "std")]
+#[derive(Debug, Clone)]
+pub struct LocalFileStore {
+    pub root: std::path::PathBuf,
+}
+
+#[cfg(feature = "std")]
+impl LocalFileStore {
+    pub fn new(root: impl Into<std::path::PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+}
+
+#[cfg(feature = "std")]
+impl ContentStore for LocalFileStore {
+    fn fetch(&self, uri: &str) -> Option<Vec<u8>> {
+        let path = match uri.strip_prefix("file://") {
+            Some(path) => std::path::PathBuf::from(path),
+            None if !uri.contains("://") => {
+                let path = std::path::Path::new(uri);
+                let confined = path.components().all(|component| {
+                    matches!(
+                        component,
+                        std::path::Component::Normal(_) | std::path::Component::CurDir
+                    )
+                });
+                if !confined {
+                    return None;
+                }
+                self.root.join(path)
+            }
+            None => return None,
+        };
+        std::fs::read(path).ok()
+    }
+}
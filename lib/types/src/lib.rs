@@ -1,12 +1,17 @@
 #![no_std]
 
 extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
 pub use crate::generated::spore::{Bytes, BytesOpt, SporeData};
 use alloc::string::String;
 use alloc::vec::Vec;
 use molecule::prelude::{Builder, Entity};
 
 pub mod generated;
+pub mod hashref;
+#[cfg(feature = "std")]
+pub mod native;
 
 impl Into<Bytes> for &[u8] {
     fn into(self) -> Bytes {
@@ -58,3 +63,21 @@ impl generated::spore::Bytes {
         &self.as_slice()[4..]
     }
 }
+
+impl generated::spore::Uint64 {
+    pub fn unpack(&self) -> u64 {
+        u64::from_le_bytes(self.as_slice().try_into().unwrap_or_default())
+    }
+}
+
+impl From<u64> for generated::spore::Uint64 {
+    fn from(value: u64) -> Self {
+        generated::spore::Uint64::new_unchecked(value.to_le_bytes().to_vec().into())
+    }
+}
+
+impl generated::mutant_rules::Uint64 {
+    pub fn unpack(&self) -> u64 {
+        u64::from_le_bytes(self.as_slice().try_into().unwrap_or_default())
+    }
+}
@@ -0,0 +1,618 @@
+// Native counterparts of molecule types in spore_v1.mol, spore_v2.mol and action.mol for off-chain
+// services, which round-trip through JSON with byte arrays in `0x` prefixed hex
+//
+// note: conversions between native and molecule types are lossless, while parsing from raw bytes
+// accepts and drops extra fields of compatible tables, e.g. a `ClusterDataV3` read as `ClusterDataV2`
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use ckb_gen_types::packed;
+use molecule::error::VerificationError;
+use molecule::prelude::{Builder, Byte, Entity};
+use serde::{Deserialize, Serialize};
+
+use crate::generated::{action, spore};
+use crate::NativeNFTData;
+
+macro_rules! native_table {
+    (
+        $(#[$meta:meta])*
+        $native:ident($entity:ty) {
+            $($(#[$field_meta:meta])* $field:ident: $ty:ty,)*
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+        pub struct $native {
+            $($(#[$field_meta])* pub $field: $ty,)*
+        }
+
+        impl From<$native> for $entity {
+            fn from(native: $native) -> Self {
+                <$entity>::new_builder()
+                    $(.$field(native.$field.into()))*
+                    .build()
+            }
+        }
+
+        impl From<$entity> for $native {
+            fn from(entity: $entity) -> Self {
+                Self {
+                    $($field: entity.$field().into(),)*
+                }
+            }
+        }
+
+        impl TryFrom<&[u8]> for $native {
+            type Error = VerificationError;
+
+            fn try_from(slice: &[u8]) -> Result<Self, Self::Error> {
+                <$entity>::from_compatible_slice(slice).map(Into::into)
+            }
+        }
+    };
+}
+
+macro_rules! native_union {
+    (
+        $(#[$meta:meta])*
+        $native:ident($entity:ty, $union:ident) {
+            $($item:ident($native_item:ty),)*
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+        pub enum $native {
+            $($item($native_item),)*
+        }
+
+        impl From<$native> for $entity {
+            fn from(native: $native) -> Self {
+                let item = match native {
+                    $($native::$item(item) => action::$union::$item(item.into()),)*
+                };
+                <$entity>::new_builder().set(item).build()
+            }
+        }
+
+        impl From<$entity> for $native {
+            fn from(entity: $entity) -> Self {
+                match entity.to_enum() {
+                    $(action::$union::$item(item) => Self::$item(item.into()),)*
+                }
+            }
+        }
+
+        impl TryFrom<&[u8]> for $native {
+            type Error = VerificationError;
+
+            fn try_from(slice: &[u8]) -> Result<Self, Self::Error> {
+                <$entity>::from_compatible_slice(slice).map(Into::into)
+            }
+        }
+
+        $(
+            impl From<$native_item> for $native {
+                fn from(item: $native_item) -> Self {
+                    Self::$item(item)
+                }
+            }
+        )*
+    };
+}
+
+native_table! {
+    NativeSporeData(spore::SporeData) {
+        #[serde(with = "hex_bytes")]
+        content_type: Vec<u8>,
+        #[serde(with = "hex_bytes")]
+        content: Vec<u8>,
+        #[serde(with = "hex_bytes_opt")]
+        cluster_id: Option<Vec<u8>>,
+    }
+}
+
+native_table! {
+    #[derive(Default)]
+    NativeClusterData(spore::ClusterData) {
+        #[serde(with = "hex_bytes")]
+        name: Vec<u8>,
+        #[serde(with = "hex_bytes")]
+        description: Vec<u8>,
+    }
+}
+
+native_table! {
+    #[derive(Default)]
+    NativeClusterDataV2(spore::ClusterDataV2) {
+        #[serde(with = "hex_bytes")]
+        name: Vec<u8>,
+        #[serde(with = "hex_bytes")]
+        description: Vec<u8>,
+        #[serde(with = "hex_bytes_opt")]
+        mutant_id: Option<Vec<u8>>,
+    }
+}
+
+native_table! {
+    #[derive(Default)]
+    NativePublicMint(spore::PublicMint) {
+        start_epoch: u64,
+        price: u64,
+    }
+}
+
+native_table! {
+    /// Cluster data of the latest version, where optional fields are set on top of `new`, e.g.
+    /// `NativeClusterDataV3 { allowlist_root: Some(root), ..NativeClusterDataV3::new(name, "") }`
+    #[derive(Default)]
+    NativeClusterDataV3(spore::ClusterDataV3) {
+        #[serde(with = "hex_bytes")]
+        name: Vec<u8>,
+        #[serde(with = "hex_bytes")]
+        description: Vec<u8>,
+        #[serde(with = "hex_bytes_opt")]
+        mutant_id: Option<Vec<u8>>,
+        #[serde(with = "hex_bytes_opt")]
+        allowlist_root: Option<Vec<u8>>,
+        public_mint: Option<NativePublicMint>,
+        #[serde(with = "hex_bytes_opt")]
+        lifecycle_mutant_id: Option<Vec<u8>>,
+    }
+}
+
+impl NativeClusterData {
+    pub fn new(name: &str, description: &str) -> Self {
+        Self {
+            name: name.as_bytes().to_vec(),
+            description: description.as_bytes().to_vec(),
+        }
+    }
+}
+
+impl NativeClusterDataV2 {
+    pub fn new(name: &str, description: &str) -> Self {
+        Self {
+            name: name.as_bytes().to_vec(),
+            description: description.as_bytes().to_vec(),
+            ..Default::default()
+        }
+    }
+}
+
+impl NativeClusterDataV3 {
+    pub fn new(name: &str, description: &str) -> Self {
+        Self {
+            name: name.as_bytes().to_vec(),
+            description: description.as_bytes().to_vec(),
+            ..Default::default()
+        }
+    }
+}
+
+impl From<NativeNFTData> for NativeSporeData {
+    fn from(nft: NativeNFTData) -> Self {
+        Self {
+            content_type: nft.content_type.into_bytes(),
+            content: nft.content,
+            cluster_id: nft.cluster_id,
+        }
+    }
+}
+
+/// Fails with the spore data itself if its content type isn't UTF-8
+impl TryFrom<NativeSporeData> for NativeNFTData {
+    type Error = NativeSporeData;
+
+    fn try_from(data: NativeSporeData) -> Result<Self, Self::Error> {
+        match String::from_utf8(data.content_type) {
+            Ok(content_type) => Ok(Self {
+                content_type,
+                content: data.content,
+                cluster_id: data.cluster_id,
+            }),
+            Err(err) => Err(NativeSporeData {
+                content_type: err.into_bytes(),
+                ..data
+            }),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NativeScript {
+    #[serde(with = "hex_byte32")]
+    pub code_hash: [u8; 32],
+    pub hash_type: u8,
+    #[serde(with = "hex_bytes")]
+    pub args: Vec<u8>,
+}
+
+impl From<NativeScript> for action::Script {
+    fn from(native: NativeScript) -> Self {
+        action::Script::new_builder()
+            .code_hash(native.code_hash.into())
+            .hash_type(Byte::new(native.hash_type))
+            .args(native.args.into())
+            .build()
+    }
+}
+
+impl From<action::Script> for NativeScript {
+    fn from(entity: action::Script) -> Self {
+        Self {
+            code_hash: entity.code_hash().into(),
+            hash_type: entity.hash_type().as_slice()[0],
+            args: entity.args().into(),
+        }
+    }
+}
+
+native_union! {
+    NativeAddress(action::Address, AddressUnion) {
+        Script(NativeScript),
+    }
+}
+
+// `action::Script` shares the same layout with CKB `Script`, and `Address` has the only variant of
+// `Script`, so lock scripts convert to addresses and back losslessly
+
+impl From<packed::Script> for action::Script {
+    fn from(script: packed::Script) -> Self {
+        action::Script::new_unchecked(script.as_bytes())
+    }
+}
+
+impl From<action::Script> for packed::Script {
+    fn from(script: action::Script) -> Self {
+        packed::Script::new_unchecked(script.as_bytes())
+    }
+}
+
+impl From<packed::Script> for action::Address {
+    fn from(script: packed::Script) -> Self {
+        action::Address::new_builder()
+            .set(action::Script::from(script))
+            .build()
+    }
+}
+
+impl From<action::Address> for packed::Script {
+    fn from(address: action::Address) -> Self {
+        let action::AddressUnion::Script(script) = address.to_enum();
+        script.into()
+    }
+}
+
+impl From<packed::Script> for NativeScript {
+    fn from(script: packed::Script) -> Self {
+        action::Script::from(script).into()
+    }
+}
+
+impl From<NativeScript> for packed::Script {
+    fn from(script: NativeScript) -> Self {
+        action::Script::from(script).into()
+    }
+}
+
+impl From<packed::Script> for NativeAddress {
+    fn from(script: packed::Script) -> Self {
+        Self::Script(script.into())
+    }
+}
+
+impl From<NativeAddress> for packed::Script {
+    fn from(address: NativeAddress) -> Self {
+        let NativeAddress::Script(script) = address;
+        script.into()
+    }
+}
+
+native_table! {
+    NativeMintSpore(action::MintSpore) {
+        #[serde(with = "hex_byte32")]
+        spore_id: [u8; 32],
+        to: NativeAddress,
+        #[serde(with = "hex_byte32")]
+        data_hash: [u8; 32],
+    }
+}
+
+native_table! {
+    NativeMintSporeWithProof(action::MintSporeWithProof) {
+        #[serde(with = "hex_byte32")]
+        spore_id: [u8; 32],
+        to: NativeAddress,
+        #[serde(with = "hex_byte32")]
+        data_hash: [u8; 32],
+        #[serde(with = "hex_byte32_vec")]
+        allowlist_proof: Vec<[u8; 32]>,
+    }
+}
+
+native_table! {
+    NativeTransferSpore(action::TransferSpore) {
+        #[serde(with = "hex_byte32")]
+        spore_id: [u8; 32],
+        from: NativeAddress,
+        to: NativeAddress,
+    }
+}
+
+native_table! {
+    NativeBurnSpore(action::BurnSpore) {
+        #[serde(with = "hex_byte32")]
+        spore_id: [u8; 32],
+        from: NativeAddress,
+    }
+}
+
+native_table! {
+    NativeMintCluster(action::MintCluster) {
+        #[serde(with = "hex_byte32")]
+        cluster_id: [u8; 32],
+        to: NativeAddress,
+        #[serde(with = "hex_byte32")]
+        data_hash: [u8; 32],
+    }
+}
+
+native_table! {
+    NativeTransferCluster(action::TransferCluster) {
+        #[serde(with = "hex_byte32")]
+        cluster_id: [u8; 32],
+        from: NativeAddress,
+        to: NativeAddress,
+    }
+}
+
+native_table! {
+    NativeMintProxy(action::MintProxy) {
+        #[serde(with = "hex_byte32")]
+        cluster_id: [u8; 32],
+        #[serde(with = "hex_byte32")]
+        proxy_id: [u8; 32],
+        to: NativeAddress,
+    }
+}
+
+native_table! {
+    NativeTransferProxy(action::TransferProxy) {
+        #[serde(with = "hex_byte32")]
+        cluster_id: [u8; 32],
+        #[serde(with = "hex_byte32")]
+        proxy_id: [u8; 32],
+        from: NativeAddress,
+        to: NativeAddress,
+    }
+}
+
+native_table! {
+    NativeBurnProxy(action::BurnProxy) {
+        #[serde(with = "hex_byte32")]
+        cluster_id: [u8; 32],
+        #[serde(with = "hex_byte32")]
+        proxy_id: [u8; 32],
+        from: NativeAddress,
+    }
+}
+
+native_table! {
+    NativeMintAgent(action::MintAgent) {
+        #[serde(with = "hex_byte32")]
+        cluster_id: [u8; 32],
+        #[serde(with = "hex_byte32")]
+        proxy_id: [u8; 32],
+        to: NativeAddress,
+    }
+}
+
+native_table! {
+    NativeTransferAgent(action::TransferAgent) {
+        #[serde(with = "hex_byte32")]
+        cluster_id: [u8; 32],
+        from: NativeAddress,
+        to: NativeAddress,
+    }
+}
+
+native_table! {
+    NativeBurnAgent(action::BurnAgent) {
+        #[serde(with = "hex_byte32")]
+        cluster_id: [u8; 32],
+        from: NativeAddress,
+    }
+}
+
+native_union! {
+    /// Serialized as `{"<Action>": {..}}` in JSON, e.g. `{"MintSpore": {"spore_id": "0x..", ..}}`
+    NativeSporeAction(action::SporeAction, SporeActionUnion) {
+        MintSpore(NativeMintSpore),
+        TransferSpore(NativeTransferSpore),
+        BurnSpore(NativeBurnSpore),
+        MintCluster(NativeMintCluster),
+        TransferCluster(NativeTransferCluster),
+        MintProxy(NativeMintProxy),
+        TransferProxy(NativeTransferProxy),
+        BurnProxy(NativeBurnProxy),
+        MintAgent(NativeMintAgent),
+        TransferAgent(NativeTransferAgent),
+        BurnAgent(NativeBurnAgent),
+        MintSporeWithProof(NativeMintSporeWithProof),
+    }
+}
+
+// byte-level conversions used by the native types above
+
+impl From<Vec<u8>> for spore::Bytes {
+    fn from(value: Vec<u8>) -> Self {
+        value.as_slice().into()
+    }
+}
+
+impl From<spore::Bytes> for Vec<u8> {
+    fn from(value: spore::Bytes) -> Self {
+        value.raw_data().to_vec()
+    }
+}
+
+impl From<Option<Vec<u8>>> for spore::BytesOpt {
+    fn from(value: Option<Vec<u8>>) -> Self {
+        spore::BytesOpt::new_builder()
+            .set(value.map(Into::into))
+            .build()
+    }
+}
+
+impl From<spore::BytesOpt> for Option<Vec<u8>> {
+    fn from(value: spore::BytesOpt) -> Self {
+        value.to_opt().map(Into::into)
+    }
+}
+
+impl From<spore::Uint64> for u64 {
+    fn from(value: spore::Uint64) -> Self {
+        value.unpack()
+    }
+}
+
+impl From<Option<NativePublicMint>> for spore::PublicMintOpt {
+    fn from(value: Option<NativePublicMint>) -> Self {
+        spore::PublicMintOpt::new_builder()
+            .set(value.map(Into::into))
+            .build()
+    }
+}
+
+impl From<spore::PublicMintOpt> for Option<NativePublicMint> {
+    fn from(value: spore::PublicMintOpt) -> Self {
+        value.to_opt().map(Into::into)
+    }
+}
+
+impl From<Vec<u8>> for action::Bytes {
+    fn from(value: Vec<u8>) -> Self {
+        action::Bytes::new_builder()
+            .set(value.into_iter().map(Byte::new).collect())
+            .build()
+    }
+}
+
+impl From<action::Bytes> for Vec<u8> {
+    fn from(value: action::Bytes) -> Self {
+        value.raw_data().to_vec()
+    }
+}
+
+impl From<[u8; 32]> for action::Byte32 {
+    fn from(value: [u8; 32]) -> Self {
+        action::Byte32::new_unchecked(value.to_vec().into())
+    }
+}
+
+impl From<action::Byte32> for [u8; 32] {
+    fn from(value: action::Byte32) -> Self {
+        let mut bytes = [0u8; 32];
+        bytes.copy_from_slice(value.as_slice());
+        bytes
+    }
+}
+
+impl From<Vec<[u8; 32]>> for action::Byte32Vec {
+    fn from(value: Vec<[u8; 32]>) -> Self {
+        action::Byte32Vec::new_builder()
+            .extend(value.into_iter().map(Into::into))
+            .build()
+    }
+}
+
+impl From<action::Byte32Vec> for Vec<[u8; 32]> {
+    fn from(value: action::Byte32Vec) -> Self {
+        value.into_iter().map(Into::into).collect()
+    }
+}
+
+// serde helpers of byte arrays in `0x` prefixed hex, and the prefix is optional in deserialization
+
+fn encode_hex(bytes: &[u8]) -> alloc::string::String {
+    alloc::format!("0x{}", hex::encode(bytes))
+}
+
+fn decode_hex<E: serde::de::Error>(value: &str) -> Result<Vec<u8>, E> {
+    hex::decode(value.strip_prefix("0x").unwrap_or(value)).map_err(E::custom)
+}
+
+fn decode_hex32<E: serde::de::Error>(value: &str) -> Result<[u8; 32], E> {
+    decode_hex(value)?
+        .try_into()
+        .map_err(|bytes: Vec<u8>| E::invalid_length(bytes.len(), &"32 bytes"))
+}
+
+mod hex_bytes {
+    use super::*;
+    use alloc::string::String;
+    use serde::{Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(value: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&encode_hex(value))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        decode_hex(&String::deserialize(deserializer)?)
+    }
+}
+
+mod hex_bytes_opt {
+    use super::*;
+    use alloc::string::String;
+    use serde::{Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        value: &Option<Vec<u8>>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        value.as_deref().map(encode_hex).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Vec<u8>>, D::Error> {
+        Option::<String>::deserialize(deserializer)?
+            .map(|value| decode_hex(&value))
+            .transpose()
+    }
+}
+
+mod hex_byte32 {
+    use super::*;
+    use alloc::string::String;
+    use serde::{Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(value: &[u8; 32], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&encode_hex(value))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<[u8; 32], D::Error> {
+        decode_hex32(&String::deserialize(deserializer)?)
+    }
+}
+
+mod hex_byte32_vec {
+    use super::*;
+    use alloc::string::String;
+    use serde::{Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(value: &[[u8; 32]], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(value.iter().map(|item| encode_hex(item)))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<[u8; 32]>, D::Error> {
+        Vec::<String>::deserialize(deserializer)?
+            .iter()
+            .map(|value| decode_hex32(value))
+            .collect()
+    }
+}
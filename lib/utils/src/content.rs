@@ -0,0 +1,384 @@
+// Opt-in content validation of spore driven by content type
+//
+// `charset=utf-8` and `charset=us-ascii` enforce the encoding of text, `application/json` and the
+// `+json` structured suffix (RFC 6838) enforce well-formed JSON (RFC 8259), and `strict=true`
+// enforces headers of `image/*`, others are unchecked. Encoded content is decoded before validation
+// if any of the checks applies or in strict mode.
+// `application/spore-ref+json` additionally requires well-formed `spore://<spore_id>` references,
+// and `application/vnd.spore.hashref` requires a well-formed content hash commitment
+
+use alloc::{borrow::Cow, vec::Vec};
+use spore_errors::error::Error;
+use spore_types::hashref::NativeContentHashRef;
+
+use crate::{check_image_header, decode_content, MAX_DECODED_CONTENT_LEN, MIME};
+
+const SPORE_REF_SUB_TYPE: &[u8] = b"spore-ref+json";
+const SPORE_REF_SCHEME: &[u8] = b"spore://";
+const HASHREF_SUB_TYPE: &[u8] = b"vnd.spore.hashref";
+
+/// Validates content against its parsed content type, `content_type` is the one `mime` parsed from
+///
+/// Returns the decoded content if encoded content has been decoded for validation, so callers can
+/// reuse it instead of decoding again
+pub fn validate_content(
+    content_type: &[u8],
+    mime: &MIME,
+    content: &[u8],
+) -> Result<Option<Vec<u8>>, Error> {
+    let checks = ContentChecks::parse(content_type, mime)?;
+    // encoded content is decoded in strict mode or whenever the content type asks for validation,
+    // since checks only make sense on decoded bytes, otherwise it stays opaque
+    match mime.encoding {
+        Some(_) if checks.is_empty() && !mime.strict => Ok(None),
+        Some(encoding) => {
+            let decoded = decode_content(encoding, content, MAX_DECODED_CONTENT_LEN)?;
+            checks.apply(content_type, mime, &decoded)?;
+            Ok(Some(decoded))
+        }
+        None => checks.apply(content_type, mime, content).map(|_| None),
+    }
+}
+
+// checks asked by content type, all of them apply to decoded content
+#[derive(Default)]
+struct ContentChecks {
+    utf8: bool,
+    ascii: bool,
+    json: bool,
+    spore_ref: bool,
+    hashref: bool,
+    image_header: bool,
+}
+
+impl ContentChecks {
+    fn parse(content_type: &[u8], mime: &MIME) -> Result<Self, Error> {
+        let mut checks = Self::default();
+        if let Some(charset) = mime.get_param_value(content_type, "charset")? {
+            checks.utf8 = charset.eq_ignore_ascii_case(b"utf-8");
+            checks.ascii = charset.eq_ignore_ascii_case(b"us-ascii");
+        }
+        let main_type = &content_type[mime.main_type.clone()];
+        let sub_type = &content_type[mime.sub_type.clone()];
+        // spore references and hashref have their own checks, which cover JSON as well
+        checks.spore_ref = is_spore_ref_content(content_type, mime);
+        checks.hashref = main_type.eq_ignore_ascii_case(b"application")
+            && sub_type.eq_ignore_ascii_case(HASHREF_SUB_TYPE);
+        checks.json = !checks.spore_ref
+            && !checks.hashref
+            && ((main_type.eq_ignore_ascii_case(b"application")
+                && sub_type.eq_ignore_ascii_case(b"json"))
+                || (sub_type.len() > 5
+                    && sub_type[sub_type.len() - 5..].eq_ignore_ascii_case(b"+json")));
+        checks.image_header = mime.strict && main_type.eq_ignore_ascii_case(b"image");
+        Ok(checks)
+    }
+
+    fn is_empty(&self) -> bool {
+        !(self.utf8
+            || self.ascii
+            || self.json
+            || self.spore_ref
+            || self.hashref
+            || self.image_header)
+    }
+
+    fn apply(&self, content_type: &[u8], mime: &MIME, content: &[u8]) -> Result<(), Error> {
+        if self.utf8 {
+            core::str::from_utf8(content).map_err(|_| Error::InvalidUtf8Content)?;
+        }
+        if self.ascii && !content.is_ascii() {
+            return Err(Error::InvalidUtf8Content);
+        }
+        if self.spore_ref {
+            extract_spore_refs(content)?;
+        }
+        if self.hashref {
+            validate_hashref(content)?;
+        }
+        if self.json {
+            validate_json(content)?;
+        }
+        if self.image_header {
+            check_image_header(&content_type[mime.sub_type.clone()], content)?;
+        }
+        Ok(())
+    }
+}
+
+/// Checks if content is a well-formed JSON text encoded in UTF-8
+pub fn validate_json(content: &[u8]) -> Result<(), Error> {
+    parse_json(content, |_| Ok(()))
+}
+
+/// Checks if content is a molecule `ContentHashRef` with a known algorithm, non-zero size and at
+/// least one non-empty URI in UTF-8, the referred content itself is verified off-chain
+pub fn validate_hashref(content: &[u8]) -> Result<(), Error> {
+    NativeContentHashRef::from_slice(content).ok_or(Error::InvalidHashRefContent)?;
+    Ok(())
+}
+
+/// Checks if content type is `application/spore-ref+json`, whose content refers to other spores
+pub fn is_spore_ref_content(content_type: &[u8], mime: &MIME) -> bool {
+    content_type[mime.main_type.clone()].eq_ignore_ascii_case(b"application")
+        && content_type[mime.sub_type.clone()].eq_ignore_ascii_case(SPORE_REF_SUB_TYPE)
+}
+
+/// Extracts ids of spores referred by `spore://<spore_id>` string values in JSON content, which
+/// requires at least one reference and `spore_id` to be 32 bytes in hex
+pub fn extract_spore_refs(content: &[u8]) -> Result<Vec<[u8; 32]>, Error> {
+    let mut refs = Vec::new();
+    parse_json(content, |value| {
+        if let Some(hex) = value.strip_prefix(SPORE_REF_SCHEME) {
+            refs.push(decode_spore_id(hex).ok_or(Error::InvalidSporeRef)?);
+        }
+        Ok(())
+    })?;
+    if refs.is_empty() {
+        return Err(Error::InvalidSporeRef);
+    }
+    Ok(refs)
+}
+
+fn decode_spore_id(hex: &[u8]) -> Option<[u8; 32]> {
+    if hex.len() != 64 || !hex.iter().all(u8::is_ascii_hexdigit) {
+        return None;
+    }
+    let mut id = [0u8; 32];
+    for (byte, pair) in id.iter_mut().zip(hex.chunks(2)) {
+        let pair = core::str::from_utf8(pair).ok()?;
+        *byte = u8::from_str_radix(pair, 16).ok()?;
+    }
+    Some(id)
+}
+
+// `on_value` receives string values without quotes and with escapes resolved, but not object keys
+fn parse_json(
+    content: &[u8],
+    on_value: impl FnMut(&[u8]) -> Result<(), Error>,
+) -> Result<(), Error> {
+    core::str::from_utf8(content).map_err(|_| Error::InvalidJsonContent)?;
+    let mut parser = JsonParser {
+        bytes: content,
+        pos: 0,
+    };
+    parser.parse(on_value)?;
+    parser.skip_ws();
+    if parser.pos != content.len() {
+        return Err(Error::InvalidJsonContent);
+    }
+    Ok(())
+}
+
+// resolves escapes of a raw string already checked by `JsonParser::parse_string`, lone surrogates
+// of `\uXXXX` are replaced by U+FFFD
+fn unescape_string(raw: &[u8]) -> Cow<'_, [u8]> {
+    if !raw.contains(&b'\\') {
+        return Cow::Borrowed(raw);
+    }
+    let mut unescaped = Vec::with_capacity(raw.len());
+    let mut pos = 0;
+    while pos < raw.len() {
+        if raw[pos] != b'\\' {
+            unescaped.push(raw[pos]);
+            pos += 1;
+            continue;
+        }
+        let escaped = raw[pos + 1];
+        pos += 2;
+        let byte = match escaped {
+            b'b' => 0x08,
+            b'f' => 0x0c,
+            b'n' => b'\n',
+            b'r' => b'\r',
+            b't' => b'\t',
+            b'u' => {
+                let high = decode_utf16_unit(&raw[pos..pos + 4]);
+                pos += 4;
+                let mut code_point = u32::from(high);
+                if (0xd800..0xdc00).contains(&high) && raw[pos..].starts_with(b"\\u") {
+                    let low = decode_utf16_unit(&raw[pos + 2..pos + 6]);
+                    if (0xdc00..0xe000).contains(&low) {
+                        code_point = 0x10000
+                            + ((u32::from(high) - 0xd800) << 10)
+                            + (u32::from(low) - 0xdc00);
+                        pos += 6;
+                    }
+                }
+                let ch = char::from_u32(code_point).unwrap_or(char::REPLACEMENT_CHARACTER);
+                unescaped.extend_from_slice(ch.encode_utf8(&mut [0u8; 4]).as_bytes());
+                continue;
+            }
+            // `"`, `\` and `/` stand for themselves
+            other => other,
+        };
+        unescaped.push(byte);
+    }
+    Cow::Owned(unescaped)
+}
+
+fn decode_utf16_unit(hex: &[u8]) -> u16 {
+    hex.iter().fold(0, |unit, digit| {
+        let value = match digit {
+            b'0'..=b'9' => digit - b'0',
+            b'a'..=b'f' => digit - b'a' + 10,
+            _ => digit - b'A' + 10,
+        };
+        (unit << 4) | u16::from(value)
+    })
+}
+
+// iterative parser, so deeply nested content can't overflow the stack of contract
+struct JsonParser<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> JsonParser<'a> {
+    fn parse(&mut self, mut on_value: impl FnMut(&[u8]) -> Result<(), Error>) -> Result<(), Error> {
+        // open containers, either `[` or `{`
+        let mut stack = Vec::new();
+        loop {
+            self.skip_ws();
+            match self.next_byte()? {
+                b'{' => {
+                    self.skip_ws();
+                    if !self.eat(b'}') {
+                        stack.push(b'{');
+                        self.parse_key()?;
+                        continue;
+                    }
+                }
+                b'[' => {
+                    self.skip_ws();
+                    if !self.eat(b']') {
+                        stack.push(b'[');
+                        continue;
+                    }
+                }
+                b'"' => on_value(&unescape_string(self.parse_string()?))?,
+                b't' => self.expect_literal(b"rue")?,
+                b'f' => self.expect_literal(b"alse")?,
+                b'n' => self.expect_literal(b"ull")?,
+                b'-' | b'0'..=b'9' => {
+                    self.pos -= 1;
+                    self.parse_number()?;
+                }
+                _ => return Err(Error::InvalidJsonContent),
+            }
+            // a value is complete, close containers until next value is expected
+            loop {
+                let Some(&container) = stack.last() else {
+                    return Ok(());
+                };
+                self.skip_ws();
+                match (container, self.next_byte()?) {
+                    (b'{', b',') => {
+                        self.parse_key()?;
+                        break;
+                    }
+                    (b'[', b',') => break,
+                    (b'{', b'}') | (b'[', b']') => {
+                        stack.pop();
+                    }
+                    _ => return Err(Error::InvalidJsonContent),
+                }
+            }
+        }
+    }
+
+    fn parse_key(&mut self) -> Result<(), Error> {
+        self.skip_ws();
+        if !self.eat(b'"') {
+            return Err(Error::InvalidJsonContent);
+        }
+        self.parse_string()?;
+        self.skip_ws();
+        if !self.eat(b':') {
+            return Err(Error::InvalidJsonContent);
+        }
+        Ok(())
+    }
+
+    // the opening quote is consumed, returns the raw string without quotes
+    fn parse_string(&mut self) -> Result<&'a [u8], Error> {
+        let start = self.pos;
+        loop {
+            match self.next_byte()? {
+                b'"' => return Ok(&self.bytes[start..self.pos - 1]),
+                b'\\' => match self.next_byte()? {
+                    b'"' | b'\\' | b'/' | b'b' | b'f' | b'n' | b'r' | b't' => {}
+                    b'u' => {
+                        for _ in 0..4 {
+                            if !self.next_byte()?.is_ascii_hexdigit() {
+                                return Err(Error::InvalidJsonContent);
+                            }
+                        }
+                    }
+                    _ => return Err(Error::InvalidJsonContent),
+                },
+                0x00..=0x1f => return Err(Error::InvalidJsonContent),
+                _ => {}
+            }
+        }
+    }
+
+    fn parse_number(&mut self) -> Result<(), Error> {
+        self.eat(b'-');
+        match self.next_byte()? {
+            b'0' => {}
+            b'1'..=b'9' => self.skip_digits(),
+            _ => return Err(Error::InvalidJsonContent),
+        }
+        if self.eat(b'.') {
+            self.expect_digits()?;
+        }
+        if self.eat(b'e') || self.eat(b'E') {
+            let _ = self.eat(b'+') || self.eat(b'-');
+            self.expect_digits()?;
+        }
+        Ok(())
+    }
+
+    fn expect_literal(&mut self, rest: &[u8]) -> Result<(), Error> {
+        if !self.bytes[self.pos..].starts_with(rest) {
+            return Err(Error::InvalidJsonContent);
+        }
+        self.pos += rest.len();
+        Ok(())
+    }
+
+    fn expect_digits(&mut self) -> Result<(), Error> {
+        if !self.next_byte()?.is_ascii_digit() {
+            return Err(Error::InvalidJsonContent);
+        }
+        self.skip_digits();
+        Ok(())
+    }
+
+    fn skip_digits(&mut self) {
+        while self.bytes.get(self.pos).is_some_and(u8::is_ascii_digit) {
+            self.pos += 1;
+        }
+    }
+
+    fn skip_ws(&mut self) {
+        while matches!(self.bytes.get(self.pos), Some(b' ' | b'\t' | b'\n' | b'\r')) {
+            self.pos += 1;
+        }
+    }
+
+    fn eat(&mut self, byte: u8) -> bool {
+        if self.bytes.get(self.pos) == Some(&byte) {
+            self.pos += 1;
+            return true;
+        }
+        false
+    }
+
+    fn next_byte(&mut self) -> Result<u8, Error> {
+        let byte = *self.bytes.get(self.pos).ok_or(Error::InvalidJsonContent)?;
+        self.pos += 1;
+        Ok(byte)
+    }
+}
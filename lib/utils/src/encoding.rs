@@ -0,0 +1,247 @@
+// Compressed content of spore, declared by `encoding` param in content type
+//
+// note: brotli can't be decoded in contracts since its decoder needs much more memory than the
+// heap of contracts, so brotli content is only decoded off-chain
+
+#[cfg(feature = "std")]
+use alloc::string::String;
+use alloc::vec::Vec;
+use spore_errors::error::Error;
+#[cfg(feature = "std")]
+use spore_types::NativeNFTData;
+
+#[cfg(feature = "std")]
+use crate::{Mime, MIME};
+
+/// Limit of decoded content, which keeps decoding in contract within memory and cycle limits
+pub const MAX_DECODED_CONTENT_LEN: usize = 1024 * 1024;
+
+const GZIP_MAGIC: &[u8] = b"\x1f\x8b";
+const GZIP_CM_DEFLATE: u8 = 8;
+const GZIP_HEADER_LEN: usize = 10;
+const GZIP_TRAILER_LEN: usize = 8;
+const GZIP_FHCRC: u8 = 0x02;
+const GZIP_FEXTRA: u8 = 0x04;
+const GZIP_FNAME: u8 = 0x08;
+const GZIP_FCOMMENT: u8 = 0x10;
+const GZIP_RESERVED_FLAGS: u8 = 0xe0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContentEncoding {
+    Gzip,
+    Zstd,
+    Brotli,
+}
+
+impl ContentEncoding {
+    /// Parses value of `encoding` param, which is case-insensitive
+    pub fn from_name(name: &[u8]) -> Option<Self> {
+        if name.eq_ignore_ascii_case(b"gzip") {
+            Some(Self::Gzip)
+        } else if name.eq_ignore_ascii_case(b"zstd") {
+            Some(Self::Zstd)
+        } else if name.eq_ignore_ascii_case(b"brotli") {
+            Some(Self::Brotli)
+        } else {
+            None
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Gzip => "gzip",
+            Self::Zstd => "zstd",
+            Self::Brotli => "brotli",
+        }
+    }
+}
+
+/// Decodes content, failing if it's corrupted or exceeds `max_len` after decoding
+pub fn decode_content(
+    encoding: ContentEncoding,
+    content: &[u8],
+    max_len: usize,
+) -> Result<Vec<u8>, Error> {
+    match encoding {
+        ContentEncoding::Gzip => decode_gzip(content, max_len),
+        ContentEncoding::Zstd => decode_zstd(content, max_len),
+        #[cfg(feature = "std")]
+        ContentEncoding::Brotli => decode_brotli(content, max_len),
+        #[cfg(not(feature = "std"))]
+        ContentEncoding::Brotli => Err(Error::UnsupportedContentEncoding),
+    }
+}
+
+/// Encodes content off-chain, e.g. before building `NativeNFTData`
+#[cfg(feature = "std")]
+pub fn encode_content(encoding: ContentEncoding, content: &[u8]) -> Vec<u8> {
+    match encoding {
+        ContentEncoding::Gzip => {
+            let mut encoded = Vec::from(*b"\x1f\x8b\x08\0\0\0\0\0\0\xff");
+            encoded.extend(miniz_oxide::deflate::compress_to_vec(content, 6));
+            encoded.extend(crc32(content).to_le_bytes());
+            encoded.extend((content.len() as u32).to_le_bytes());
+            encoded
+        }
+        ContentEncoding::Zstd => {
+            use std::io::Write;
+
+            // checksum lets contracts verify decoded content
+            let mut encoder = zstd::Encoder::new(Vec::new(), 0).expect("zstd encoder");
+            encoder.include_checksum(true).expect("zstd checksum");
+            encoder.write_all(content).expect("write to vec");
+            encoder.finish().expect("write to vec")
+        }
+        ContentEncoding::Brotli => {
+            let mut encoded = Vec::new();
+            let params = brotli::enc::BrotliEncoderParams::default();
+            brotli::enc::BrotliCompress(&mut &content[..], &mut encoded, &params)
+                .expect("write to vec");
+            encoded
+        }
+    }
+}
+
+/// Encodes content of NFT and declares the encoding in its content type
+#[cfg(feature = "std")]
+pub fn encode_nft_content(
+    nft: NativeNFTData,
+    encoding: ContentEncoding,
+) -> Result<NativeNFTData, Error> {
+    let mime = Mime::parse(nft.content_type.as_bytes())?;
+    if mime.get_param("encoding").is_some() {
+        return Err(Error::InvalidContentType);
+    }
+    let content_type = mime.param("encoding", encoding.name()).to_bytes()?;
+    Ok(NativeNFTData {
+        content_type: String::from_utf8(content_type).expect("serialized content type"),
+        content: encode_content(encoding, &nft.content),
+        cluster_id: nft.cluster_id,
+    })
+}
+
+/// Decodes content of NFT by the encoding in its content type, or returns the raw content
+#[cfg(feature = "std")]
+pub fn decode_nft_content(nft: &NativeNFTData) -> Result<Vec<u8>, Error> {
+    match MIME::parse(nft.content_type.as_bytes())?.encoding {
+        Some(encoding) => decode_content(encoding, &nft.content, MAX_DECODED_CONTENT_LEN),
+        None => Ok(nft.content.clone()),
+    }
+}
+
+// single member gzip of RFC 1952, whose CRC32 and size in trailer must match decoded content
+fn decode_gzip(content: &[u8], max_len: usize) -> Result<Vec<u8>, Error> {
+    if content.len() < GZIP_HEADER_LEN + GZIP_TRAILER_LEN
+        || !content.starts_with(GZIP_MAGIC)
+        || content[2] != GZIP_CM_DEFLATE
+    {
+        return Err(Error::InvalidEncodedContent);
+    }
+    let flags = content[3];
+    if flags & GZIP_RESERVED_FLAGS != 0 {
+        return Err(Error::InvalidEncodedContent);
+    }
+    let (body, trailer) = content.split_at(content.len() - GZIP_TRAILER_LEN);
+    let mut offset = GZIP_HEADER_LEN;
+    if flags & GZIP_FEXTRA != 0 {
+        let extra_len = body
+            .get(offset..offset + 2)
+            .ok_or(Error::InvalidEncodedContent)?;
+        offset += 2 + u16::from_le_bytes([extra_len[0], extra_len[1]]) as usize;
+    }
+    for flag in [GZIP_FNAME, GZIP_FCOMMENT] {
+        if flags & flag != 0 {
+            let terminator = body
+                .get(offset..)
+                .and_then(|rest| rest.iter().position(|&b| b == 0))
+                .ok_or(Error::InvalidEncodedContent)?;
+            offset += terminator + 1;
+        }
+    }
+    if flags & GZIP_FHCRC != 0 {
+        offset += 2;
+    }
+    let deflated = body.get(offset..).ok_or(Error::InvalidEncodedContent)?;
+    let decoded = miniz_oxide::inflate::decompress_to_vec_with_limit(deflated, max_len)
+        .map_err(|_| Error::InvalidEncodedContent)?;
+    let (crc, size) = trailer.split_at(4);
+    if crc != crc32(&decoded).to_le_bytes() || size != (decoded.len() as u32).to_le_bytes() {
+        return Err(Error::InvalidEncodedContent);
+    }
+    Ok(decoded)
+}
+
+// single zstd frame without trailing data, whose checksum must match if present
+fn decode_zstd(content: &[u8], max_len: usize) -> Result<Vec<u8>, Error> {
+    use ruzstd::io::Read;
+
+    let mut source = content;
+    let mut decoder =
+        ruzstd::StreamingDecoder::new(&mut source).map_err(|_| Error::InvalidEncodedContent)?;
+    let mut decoded = Vec::new();
+    let mut buffer = [0u8; 4096];
+    loop {
+        let len = decoder
+            .read(&mut buffer)
+            .map_err(|_| Error::InvalidEncodedContent)?;
+        if len == 0 {
+            break;
+        }
+        if decoded.len() + len > max_len {
+            return Err(Error::InvalidEncodedContent);
+        }
+        decoded.extend_from_slice(&buffer[..len]);
+    }
+    // checksum is optional in frame
+    let frame = decoder.into_frame_decoder();
+    if let Some(checksum) = frame.get_checksum_from_data() {
+        if frame.get_calculated_checksum() != Some(checksum) {
+            return Err(Error::InvalidEncodedContent);
+        }
+    }
+    if !source.is_empty() {
+        return Err(Error::InvalidEncodedContent);
+    }
+    Ok(decoded)
+}
+
+#[cfg(feature = "std")]
+fn decode_brotli(content: &[u8], max_len: usize) -> Result<Vec<u8>, Error> {
+    use std::io::Read;
+
+    let mut decoded = Vec::new();
+    brotli::Decompressor::new(content, 4096)
+        .take(max_len as u64 + 1)
+        .read_to_end(&mut decoded)
+        .map_err(|_| Error::InvalidEncodedContent)?;
+    if decoded.len() > max_len {
+        return Err(Error::InvalidEncodedContent);
+    }
+    Ok(decoded)
+}
+
+// CRC-32/ISO-HDLC used by gzip
+fn crc32(data: &[u8]) -> u32 {
+    const TABLE: [u32; 256] = {
+        let mut table = [0u32; 256];
+        let mut i = 0;
+        while i < 256 {
+            let mut crc = i as u32;
+            let mut bit = 0;
+            while bit < 8 {
+                crc = if crc & 1 != 0 {
+                    (crc >> 1) ^ 0xedb8_8320
+                } else {
+                    crc >> 1
+                };
+                bit += 1;
+            }
+            table[i] = crc;
+            i += 1;
+        }
+        table
+    };
+    !data.iter().fold(!0u32, |crc, &byte| {
+        TABLE[((crc ^ byte as u32) & 0xff) as usize] ^ (crc >> 8)
+    })
+}
@@ -0,0 +1,111 @@
+// Header sanity checks of image spores, enabled by `strict=true` in content type
+//
+// note: only magic bytes and the basic structure are checked, images are not decoded
+
+use spore_errors::error::Error;
+
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+const PNG_IEND_CHUNK: &[u8] = b"\0\0\0\0IEND\xae\x42\x60\x82";
+const JPEG_SOI: &[u8] = b"\xff\xd8\xff";
+const JPEG_EOI: &[u8] = b"\xff\xd9";
+const GIF_TRAILER: u8 = 0x3b;
+
+/// Checks magic bytes and header of image content by its sub type, unsupported sub types are
+/// rejected since they can't be verified
+pub fn check_image_header(sub_type: &[u8], content: &[u8]) -> Result<(), Error> {
+    let valid = if sub_type.eq_ignore_ascii_case(b"png") {
+        is_valid_png(content)
+    } else if sub_type.eq_ignore_ascii_case(b"jpeg") {
+        is_valid_jpeg(content)
+    } else if sub_type.eq_ignore_ascii_case(b"gif") {
+        is_valid_gif(content)
+    } else if sub_type.eq_ignore_ascii_case(b"webp") {
+        is_valid_webp(content)
+    } else if sub_type.eq_ignore_ascii_case(b"svg+xml") {
+        is_valid_svg(content)
+    } else {
+        false
+    };
+    if !valid {
+        return Err(Error::InvalidImageContent);
+    }
+    Ok(())
+}
+
+// signature, then IHDR as the first chunk and IEND as the last one
+fn is_valid_png(content: &[u8]) -> bool {
+    let Some(ihdr) = content.strip_prefix(PNG_SIGNATURE) else {
+        return false;
+    };
+    if ihdr.len() < 25 + PNG_IEND_CHUNK.len() || &ihdr[..8] != b"\0\0\0\x0dIHDR" {
+        return false;
+    }
+    let width = u32::from_be_bytes(ihdr[8..12].try_into().unwrap());
+    let height = u32::from_be_bytes(ihdr[12..16].try_into().unwrap());
+    let (bit_depth, color_type) = (ihdr[16], ihdr[17]);
+    let valid_depth = match color_type {
+        0 => matches!(bit_depth, 1 | 2 | 4 | 8 | 16),
+        3 => matches!(bit_depth, 1 | 2 | 4 | 8),
+        2 | 4 | 6 => matches!(bit_depth, 8 | 16),
+        _ => false,
+    };
+    width != 0 && height != 0 && valid_depth && content.ends_with(PNG_IEND_CHUNK)
+}
+
+// SOI followed by a marker, and EOI at the end
+fn is_valid_jpeg(content: &[u8]) -> bool {
+    content.len() >= JPEG_SOI.len() + JPEG_EOI.len()
+        && content.starts_with(JPEG_SOI)
+        && content.ends_with(JPEG_EOI)
+}
+
+// header, logical screen descriptor and trailer
+fn is_valid_gif(content: &[u8]) -> bool {
+    if content.len() < 14 || !(content.starts_with(b"GIF87a") || content.starts_with(b"GIF89a")) {
+        return false;
+    }
+    let width = u16::from_le_bytes([content[6], content[7]]);
+    let height = u16::from_le_bytes([content[8], content[9]]);
+    width != 0 && height != 0 && content.last() == Some(&GIF_TRAILER)
+}
+
+// RIFF container of exact size, with a VP8 / VP8L / VP8X chunk first
+fn is_valid_webp(content: &[u8]) -> bool {
+    if content.len() < 20 || &content[..4] != b"RIFF" || &content[8..12] != b"WEBP" {
+        return false;
+    }
+    let riff_size = u32::from_le_bytes(content[4..8].try_into().unwrap()) as usize;
+    riff_size.checked_add(8) == Some(content.len())
+        && matches!(&content[12..16], b"VP8 " | b"VP8L" | b"VP8X")
+}
+
+// UTF-8 text whose root element is `svg`, after optional XML declaration, comments and doctype
+fn is_valid_svg(content: &[u8]) -> bool {
+    let Ok(text) = core::str::from_utf8(content) else {
+        return false;
+    };
+    let mut rest = text.trim_start_matches('\u{feff}').trim();
+    loop {
+        let skipped = if rest.starts_with("<?") {
+            rest.find("?>").map(|end| end + 2)
+        } else if rest.starts_with("<!--") {
+            rest.find("-->").map(|end| end + 3)
+        } else if rest.starts_with("<!") {
+            rest.find('>').map(|end| end + 1)
+        } else {
+            break;
+        };
+        let Some(skipped) = skipped else {
+            return false;
+        };
+        rest = rest[skipped..].trim_start();
+    }
+    let Some(root) = rest.strip_prefix("<svg") else {
+        return false;
+    };
+    matches!(
+        root.bytes().next(),
+        Some(b' ' | b'\t' | b'\r' | b'\n' | b'>' | b'/')
+    ) && (rest.ends_with("</svg>")
+        || (rest.ends_with("/>") && !rest[..rest.len() - 2].contains('>')))
+}
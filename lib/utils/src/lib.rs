@@ -1,36 +1,89 @@
 #![no_std]
 
 extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
 
+use alloc::ffi::CString;
+use alloc::format;
 use alloc::vec::Vec;
 use ckb_std::ckb_constants::Source;
 use ckb_std::ckb_types::bytes::Bytes;
+use ckb_std::ckb_types::core::ScriptHashType;
 use ckb_std::ckb_types::packed::Script;
 use ckb_std::ckb_types::prelude::*;
 use ckb_std::ckb_types::util::hash::Blake2bBuilder;
 use ckb_std::debug;
+use ckb_std::env::Arg;
 use ckb_std::high_level::{
-    load_cell, load_cell_data, load_cell_lock, load_cell_lock_hash, load_cell_type,
-    load_cell_type_hash, load_input, load_script, load_script_hash, QueryIter,
+    decode_hex, exec_cell, load_cell, load_cell_data, load_cell_lock, load_cell_lock_hash,
+    load_cell_type, load_cell_type_hash, load_input, load_script, load_script_hash, QueryIter,
 };
+use core::cmp::Ordering;
 
 use spore_errors::error::Error;
+use spore_types::generated::mutant_data::MutantDataReader;
 use spore_types::generated::{action, spore};
 
+pub use content::{
+    extract_spore_refs, is_spore_ref_content, validate_content, validate_hashref, validate_json,
+};
+pub use encoding::{decode_content, ContentEncoding, MAX_DECODED_CONTENT_LEN};
+#[cfg(feature = "std")]
+pub use encoding::{decode_nft_content, encode_content, encode_nft_content};
+pub use image::check_image_header;
 pub use mime::MIME;
+#[cfg(feature = "std")]
+pub use mime_owned::{Mime, MutantRef};
+pub use multipart::{extract_multipart_parts, parse_multipart, MultipartPart, MAX_MULTIPART_PARTS};
 pub mod co_build_types {
     pub use ckb_transaction_cobuild::schemas::basic::*;
     pub use ckb_transaction_cobuild::schemas::top_level::*;
 }
 
+mod content;
+mod encoding;
+mod image;
+pub mod lua;
+pub mod merkle;
 mod mime;
+#[cfg(feature = "std")]
+mod mime_owned;
+mod multipart;
 
 pub const MUTANT_ID_LEN: usize = 32;
 pub const MUTANT_ID_WITH_PAYMENT_LEN: usize = MUTANT_ID_LEN + 8;
+pub const MUTANT_ID_WITH_MODE_LEN: usize = MUTANT_ID_WITH_PAYMENT_LEN + 1;
+pub const MUTANT_ID_WITH_TIER_LEN: usize = MUTANT_ID_WITH_MODE_LEN + 1;
+pub const MUTANT_ID_WITH_RECIPIENT_LEN: usize = MUTANT_ID_WITH_PAYMENT_LEN + 32;
+pub const MUTANT_ID_WITH_OP_PAYMENTS_LEN: usize = MUTANT_ID_WITH_RECIPIENT_LEN + 16;
+pub const MUTANT_MODE_NORMAL: u8 = 0;
+pub const MUTANT_MODE_UPDATABLE: u8 = 1;
+pub const MUTANT_VERSION_LEN: usize = 8;
+
+// opcodes of spore_extension_lua external entry
+pub const MUTANT_OP_SPORE_MINT: usize = 0;
+pub const MUTANT_OP_SPORE_TRANSFER: usize = 1;
+pub const MUTANT_OP_SPORE_BURN: usize = 2;
+pub const MUTANT_OP_CLUSTER_TRANSFER: usize = 3;
+pub const MUTANT_OP_PROXY_CREATION: usize = 4;
+pub const MUTANT_OP_AGENT_CREATION: usize = 5;
+
+/// Marks the end of mutants of current extension in argv, followed by hex code hash of the next
+/// extension and pairs of its mutants
+pub const MUTANT_ARGV_NEXT_EXTENSION: &str = "next";
 
 pub const CLUSTER_PROXY_ID_LEN: usize = 32;
 pub const CLUSTER_PROXY_ID_WITH_PAYMENT_LEN: usize = CLUSTER_PROXY_ID_LEN + 8;
 
+const SINCE_FLAGS_MASK: u64 = 0xFF00_0000_0000_0000;
+const SINCE_ABSOLUTE_EPOCH_FLAGS: u64 = 0x2000_0000_0000_0000;
+const SINCE_EPOCH_NUMBER_MASK: u64 = 0x0000_0000_00FF_FFFF;
+const SINCE_RELATIVE_FLAG: u64 = 0x8000_0000_0000_0000;
+const SINCE_METRIC_MASK: u64 = 0x6000_0000_0000_0000;
+const SINCE_METRIC_EPOCH: u64 = 0x2000_0000_0000_0000;
+const SINCE_VALUE_MASK: u64 = 0x00FF_FFFF_FFFF_FFFF;
+
 pub fn load_self_id() -> Result<Vec<u8>, Error> {
     Ok(load_script()?.args().raw_data()[..32].to_vec())
 }
@@ -153,6 +206,44 @@ pub fn find_position_by_lock_hash(lock_hash: &[u8; 32], source: Source) -> Optio
     QueryIter::new(load_cell_lock_hash, source).position(|hash| hash[..] == lock_hash[..])
 }
 
+/// Returns the epoch number if `since` is in absolute epoch metric, fraction part is ignored
+pub fn parse_absolute_epoch_since(since: u64) -> Option<u64> {
+    if since & SINCE_FLAGS_MASK != SINCE_ABSOLUTE_EPOCH_FLAGS {
+        return None;
+    }
+    Some(since & SINCE_EPOCH_NUMBER_MASK)
+}
+
+pub fn is_relative_since(since: u64) -> bool {
+    since & SINCE_FLAGS_MASK & !SINCE_METRIC_MASK == SINCE_RELATIVE_FLAG
+        && since & SINCE_METRIC_MASK != SINCE_METRIC_MASK
+}
+
+/// Checks that a relative since lasts no shorter than the required one in the same metric
+pub fn is_relative_since_satisfied(since: u64, required: u64) -> bool {
+    if !is_relative_since(since) || since & SINCE_FLAGS_MASK != required & SINCE_FLAGS_MASK {
+        return false;
+    }
+    let (value, required) = (since & SINCE_VALUE_MASK, required & SINCE_VALUE_MASK);
+    if since & SINCE_METRIC_MASK == SINCE_METRIC_EPOCH {
+        compare_epoch_with_fraction(value, required) != Ordering::Less
+    } else {
+        value >= required
+    }
+}
+
+// epoch is encoded as `number | index << 24 | length << 40`, which means `number + index / length`
+fn compare_epoch_with_fraction(lhs: u64, rhs: u64) -> Ordering {
+    let fraction = |epoch: u64| {
+        let number = (epoch & SINCE_EPOCH_NUMBER_MASK) as u128;
+        let index = ((epoch >> 24) & 0xFFFF) as u128;
+        let length = ((epoch >> 40) & 0xFFFF).max(1) as u128;
+        (number * length + index, length)
+    };
+    let ((lhs, lhs_length), (rhs, rhs_length)) = (fraction(lhs), fraction(rhs));
+    (lhs * rhs_length).cmp(&(rhs * lhs_length))
+}
+
 pub fn calc_capacity_sum(lock_hash: &[u8; 32], source: Source) -> u64 {
     QueryIter::new(load_cell, source)
         .filter(|cell| cell.lock().calc_script_hash().raw_data().as_ref() == lock_hash)
@@ -191,20 +282,229 @@ pub fn extract_spore_action() -> Result<action::SporeAction, Error> {
 
 pub fn compatible_load_cluster_data(
     raw_cluster_data: &[u8],
-) -> Result<spore::ClusterDataV2, Error> {
+) -> Result<spore::ClusterDataV3, Error> {
     let cluster_data = spore::ClusterData::from_compatible_slice(raw_cluster_data)
         .map_err(|_| Error::InvalidClusterData)?;
     debug!("cluster_data filed count: {}", cluster_data.field_count());
-    if cluster_data.field_count() == 2 {
-        Ok(spore::ClusterDataV2::new_builder()
+    match cluster_data.field_count() {
+        2 => Ok(spore::ClusterDataV3::new_builder()
             .name(cluster_data.name())
             .description(cluster_data.description())
             .mutant_id(Default::default())
-            .build())
-    } else {
-        Ok(
-            spore::ClusterDataV2::from_compatible_slice(raw_cluster_data)
+            .allowlist_root(Default::default())
+            .public_mint(Default::default())
+            .lifecycle_mutant_id(Default::default())
+            .build()),
+        3 => {
+            let cluster_data = spore::ClusterDataV2::from_compatible_slice(raw_cluster_data)
+                .map_err(|_| Error::InvalidClusterData)?;
+            Ok(spore::ClusterDataV3::new_builder()
+                .name(cluster_data.name())
+                .description(cluster_data.description())
+                .mutant_id(cluster_data.mutant_id())
+                .allowlist_root(Default::default())
+                .public_mint(Default::default())
+                .lifecycle_mutant_id(Default::default())
+                .build())
+        }
+        _ => Ok(
+            spore::ClusterDataV3::from_compatible_slice(raw_cluster_data)
                 .map_err(|_| Error::InvalidClusterData)?,
-        )
+        ),
     }
 }
+
+/// Payment recipient lock hash placed after payment in mutant args, e.g.
+/// `<MUTANT_ID><PAYMENT><RECIPIENT_LOCK_HASH>`, payment goes to the mutant cell's lock if absent
+///
+/// an all-zero lock hash is rejected, since no cell can be locked by it and payments to it could
+/// never be made
+pub fn mutant_payment_recipient(args: &[u8]) -> Result<Option<[u8; 32]>, Error> {
+    let Some(lock_hash) = args.get(MUTANT_ID_WITH_PAYMENT_LEN..MUTANT_ID_WITH_RECIPIENT_LEN) else {
+        return Ok(None);
+    };
+    if lock_hash.iter().all(|byte| *byte == 0) {
+        return Err(Error::InvalidExtensionPaymentFormat);
+    }
+    Ok(lock_hash.try_into().ok())
+}
+
+/// Payment required by an operation of mutant, the mint payment is the one right after mutant id,
+/// and transfer and burn payments can follow the recipient, i.e.
+/// `<MUTANT_ID><MINT_PAYMENT><RECIPIENT_LOCK_HASH><TRANSFER_PAYMENT><BURN_PAYMENT>`
+pub fn mutant_payment(args: &[u8], opcode: usize) -> Option<u64> {
+    let offset = match opcode {
+        MUTANT_OP_SPORE_MINT => MUTANT_ID_LEN,
+        MUTANT_OP_SPORE_TRANSFER if args.len() >= MUTANT_ID_WITH_OP_PAYMENTS_LEN => {
+            MUTANT_ID_WITH_RECIPIENT_LEN
+        }
+        MUTANT_OP_SPORE_BURN if args.len() >= MUTANT_ID_WITH_OP_PAYMENTS_LEN => {
+            MUTANT_ID_WITH_RECIPIENT_LEN + 8
+        }
+        _ => return None,
+    };
+    let payment = args.get(offset..offset + 8)?;
+    Some(u64::from_le_bytes(payment.try_into().unwrap_or_default()))
+}
+
+/// Offset of the mode and memory tier bytes in mutant args, which follow the payment recipient and
+/// per-operation payments if any
+pub fn mutant_mode_offset(args: &[u8]) -> usize {
+    if args.len() >= MUTANT_ID_WITH_OP_PAYMENTS_LEN {
+        MUTANT_ID_WITH_OP_PAYMENTS_LEN
+    } else if args.len() >= MUTANT_ID_WITH_RECIPIENT_LEN {
+        MUTANT_ID_WITH_RECIPIENT_LEN
+    } else {
+        MUTANT_ID_WITH_PAYMENT_LEN
+    }
+}
+
+/// Updatable mutant is marked by a mode byte after payment in args, e.g. `<MUTANT_ID><PAYMENT><0x01>`,
+/// which can be followed by a memory tier byte
+pub fn is_updatable_mutant(args: &[u8]) -> bool {
+    let offset = mutant_mode_offset(args);
+    matches!(args.len().checked_sub(offset), Some(1 | 2)) && args[offset] == MUTANT_MODE_UPDATABLE
+}
+
+/// Splits mutant data into version and Lua code, only updatable mutant has a version prefix,
+/// e.g. `<VERSION><LUA_CODE>`
+pub fn split_mutant_data<'a>(
+    args: &[u8],
+    data: &'a [u8],
+) -> Result<(Option<u64>, &'a [u8]), Error> {
+    if !is_updatable_mutant(args) {
+        return Ok((None, data));
+    }
+    if data.len() < MUTANT_VERSION_LEN {
+        return Err(Error::InvalidMutantVersion);
+    }
+    let (version, code) = data.split_at(MUTANT_VERSION_LEN);
+    let version = u64::from_le_bytes(version.try_into().unwrap_or_default());
+    Ok((Some(version), code))
+}
+
+/// Parses mutant data (version prefix excluded) as a molecule `MutantData`, or returns `None` if
+/// it's raw Lua code in the legacy format
+///
+/// note: a molecule table begins with its total size in u32 LE, which raw Lua code can't match
+/// since 4 bytes of text are never less than 0x09090909
+pub fn parse_mutant_data(data: &[u8]) -> Result<Option<MutantDataReader<'_>>, Error> {
+    let total_size = data
+        .get(..4)
+        .map(|size| u32::from_le_bytes(size.try_into().unwrap_or_default()) as usize);
+    if total_size != Some(data.len()) {
+        return Ok(None);
+    }
+    MutantDataReader::from_compatible_slice(data)
+        .map(Some)
+        .map_err(|_| Error::InvalidMutantData)
+}
+
+/// Extracts Lua code from mutant data (version prefix excluded), which is either raw Lua code in the
+/// legacy format or a molecule `MutantData` carrying metadata along with code
+pub fn extract_mutant_code(data: &[u8]) -> Result<&[u8], Error> {
+    let Some(mutant_data) = parse_mutant_data(data)? else {
+        return Ok(data);
+    };
+    let metadata = [
+        Some(mutant_data.name()),
+        Some(mutant_data.description()),
+        Some(mutant_data.author()),
+        mutant_data.abi().to_opt(),
+    ];
+    let errors = mutant_data.errors();
+    if metadata
+        .into_iter()
+        .flatten()
+        .chain(errors.iter().map(|error| error.message()))
+        .any(|field| core::str::from_utf8(field.raw_data()).is_err())
+    {
+        return Err(Error::InvalidMutantData);
+    }
+    // every application error code is declared at most once
+    let mut declared_codes = 0u32;
+    for error in errors.iter() {
+        let code = error.code().as_slice()[0];
+        if code > lua::MUTANT_ERROR_CODE_MAX || declared_codes & (1 << code) != 0 {
+            return Err(Error::InvalidMutantData);
+        }
+        declared_codes |= 1 << code;
+    }
+    Ok(mutant_data.code().raw_data())
+}
+
+pub fn find_mutant_position(mutant_id: &[u8], mutant_code_hashes: &[[u8; 32]]) -> Option<usize> {
+    QueryIter::new(load_cell_type, Source::CellDep).position(|script| match script {
+        Some(script) => {
+            let args = script.args().raw_data();
+            mutant_code_hashes.contains(&script.code_hash().unpack())
+                && args.len() >= MUTANT_ID_LEN
+                && args[..MUTANT_ID_LEN] == mutant_id[..]
+        }
+        None => false,
+    })
+}
+
+/// Splits pairs of mutant index and args of current extension from argv, and the rest of argv
+/// starting with code hash of the next extension if any
+pub fn split_next_extension(argv: &[Arg]) -> (&[Arg], Option<&[Arg]>) {
+    let next = argv
+        .iter()
+        .step_by(2)
+        .position(|arg| arg.to_bytes() == MUTANT_ARGV_NEXT_EXTENSION.as_bytes());
+    match next {
+        Some(position) => (&argv[..position * 2], Some(&argv[position * 2 + 1..])),
+        None => (argv, None),
+    }
+}
+
+/// Executes the next extension with fixed argv followed by the rest argv except the code hash,
+/// i.e. `[code_hash, mutant_index_1, mutant_args_1, ..]`
+///
+/// note: exec never returns once succeeded, so it must be the last step of verification
+pub fn exec_next_extension(fixed_argv: &[Arg], rest_argv: &[Arg]) -> Result<(), Error> {
+    let (code_hash, mutants_argv) = rest_argv.split_first().ok_or(Error::InvalidParamValue)?;
+    let code_hash = CString::new(code_hash.to_bytes()).map_err(|_| Error::InvalidParamValue)?;
+    let code_hash: [u8; 32] = decode_hex(code_hash.as_c_str())
+        .ok()
+        .and_then(|code_hash| code_hash.try_into().ok())
+        .ok_or(Error::InvalidParamValue)?;
+    let argv = fixed_argv
+        .iter()
+        .chain(mutants_argv)
+        .map(|arg| CString::new(arg.to_bytes()).map_err(|_| Error::InvalidParamValue))
+        .collect::<Result<Vec<_>, _>>()?;
+    let argv = argv.iter().map(CString::as_c_str).collect::<Vec<_>>();
+    debug!("run next extension: {code_hash:?}");
+    exec_cell(&code_hash, ScriptHashType::Data1, &argv)?;
+    Ok(())
+}
+
+/// Executes the lifecycle mutant of cluster if set, with argv of `[opcode, mutant_index, indices..]`
+///
+/// note: exec never returns once the mutant is found, so it must be the last step of verification
+pub fn exec_cluster_lifecycle_mutant(
+    cluster_data: &spore::ClusterDataV3,
+    mutant_code_hashes: &[[u8; 32]],
+    opcode: usize,
+    indices: &[usize],
+) -> Result<(), Error> {
+    let Some(mutant_id) = cluster_data.lifecycle_mutant_id().to_opt() else {
+        return Ok(());
+    };
+    let mutant_index = find_mutant_position(&mutant_id.raw_data(), mutant_code_hashes)
+        .ok_or(Error::MutantNotInDeps)?;
+    let code_hash: [u8; 32] = load_cell_type(mutant_index, Source::CellDep)?
+        .unwrap_or_default()
+        .code_hash()
+        .unpack();
+    let argv = [opcode, mutant_index]
+        .iter()
+        .chain(indices)
+        .map(|value| CString::new(format!("{value}")).unwrap_or_default())
+        .collect::<Vec<_>>();
+    let argv = argv.iter().map(CString::as_c_str).collect::<Vec<_>>();
+    debug!("run lifecycle mutant({mutant_index}) with opcode {opcode}");
+    exec_cell(&code_hash, ScriptHashType::Data1, &argv)?;
+    Ok(())
+}
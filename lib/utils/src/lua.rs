@@ -0,0 +1,214 @@
+// Lua related rules of mutant, shared by spore_extension_lua and on-chain validations
+
+use ckb_std::ckb_types::prelude::*;
+use spore_errors::error::Error;
+
+use crate::{mutant_mode_offset, parse_mutant_data};
+
+/// Entry points every mutant must declare, called in minting, transfer and melt modes respectively
+pub const MUTANT_ENTRY_POINTS: [&str; 3] = ["on_mint", "on_transfer", "on_burn"];
+
+/// Optional entry points called in cluster lifecycle modes
+pub const MUTANT_LIFECYCLE_ENTRY_POINTS: [&str; 3] =
+    ["on_cluster_transfer", "on_proxy_create", "on_agent_create"];
+
+/// Lua heap sizes of memory tiers, which is specified by the last byte of mutant args, e.g.
+/// `<MUTANT_ID><PAYMENT><MODE><TIER>` or `<MUTANT_ID><PAYMENT><RECIPIENT_LOCK_HASH><MODE><TIER>`
+pub const LUA_MEMORY_TIERS: [usize; 4] = [128 * 1024, 256 * 1024, 500 * 1024, 1024 * 1024];
+
+/// Memory tier of mutants which don't specify one, that is the heap size used before tiers
+pub const LUA_DEFAULT_MEMORY_TIER: u8 = 2;
+
+/// Returns the Lua heap size required by mutant args
+pub fn lua_memory_size(args: &[u8]) -> Result<usize, Error> {
+    let offset = mutant_mode_offset(args);
+    let tier = if args.len() == offset + 2 {
+        args[offset + 1]
+    } else {
+        LUA_DEFAULT_MEMORY_TIER
+    };
+    LUA_MEMORY_TIERS
+        .get(tier as usize)
+        .copied()
+        .ok_or(Error::InvalidExtensionArg)
+}
+
+/// Exit codes from `MUTANT_ERROR_CODE_BASE` on are reserved for application errors of mutants, a
+/// mutant reports its own error `n` by `spore_error(n)`, which is distinguishable from failures of
+/// Lua VM or library
+pub const MUTANT_ERROR_CODE_BASE: i8 = 110;
+
+/// The largest application error code, exit codes above are reserved for validation
+pub const MUTANT_ERROR_CODE_MAX: u8 = 15;
+
+/// Returns the application error code of mutant carried by an exit code, if any
+pub fn mutant_error_code(exit_code: i8) -> Option<u8> {
+    let code = exit_code.checked_sub(MUTANT_ERROR_CODE_BASE)?;
+    (0..=MUTANT_ERROR_CODE_MAX as i8)
+        .contains(&code)
+        .then_some(code as u8)
+}
+
+/// Application error of mutant decoded from the exit code of a failed transaction
+#[derive(Debug, PartialEq, Eq)]
+pub struct MutantError<'a> {
+    pub code: u8,
+    pub message: Option<&'a str>,
+}
+
+/// Decodes an exit code of mutant with the message declared in `errors` of its `MutantData`, the
+/// data is the one of mutant cell with version prefix excluded, and the message is absent if the
+/// code isn't declared or the data is raw Lua code
+pub fn decode_mutant_error(exit_code: i8, mutant_data: &[u8]) -> Option<MutantError<'_>> {
+    let code = mutant_error_code(exit_code)?;
+    let message = parse_mutant_data(mutant_data)
+        .ok()
+        .flatten()
+        .and_then(|mutant_data| {
+            // readers got by index borrow the data instead of `errors`, so the message can outlive it
+            let errors = mutant_data.errors();
+            (0..errors.len())
+                .map(|i| errors.get_unchecked(i))
+                .find(|error| error.code().as_slice()[0] == code)
+        })
+        .and_then(|error| core::str::from_utf8(error.message().raw_data()).ok());
+    Some(MutantError { code, message })
+}
+
+/// Read-only subset of `ckb` Lua API available to mutants, constants (e.g. `ckb.SOURCE_INPUT`) are
+/// always allowed
+pub const CKB_API_WHITELIST: [&str; 4] = ["exit", "exit_script", "debug", "dump"];
+
+/// Prefixes of whitelisted `ckb` Lua API, e.g. `ckb.load_cell_data`
+pub const CKB_API_WHITELIST_PREFIXES: [&str; 2] = ["load_", "unpack_"];
+
+// identifiers which are able to reach `ckb` table in an indirect way
+const FORBIDDEN_IDENTIFIERS: [&str; 2] = ["_G", "_ENV"];
+
+/// Checks that Lua code only accesses the whitelisted `ckb` API in the form of `ckb.name`
+///
+/// note: a lightweight lexical check to reject forbidden API early in creation, only strings and
+/// comments are recognized, the restriction itself is enforced by the sandbox mutants run in
+pub fn check_ckb_api_usage(code: &[u8]) -> Result<(), Error> {
+    let mut pos = 0;
+    while pos < code.len() {
+        match code[pos] {
+            b'-' if code.get(pos + 1) == Some(&b'-') => {
+                pos = match long_bracket_level(code, pos + 2) {
+                    Some(level) => skip_long_bracket(code, pos + 2, level)?,
+                    None => skip_line(code, pos + 2),
+                };
+            }
+            b'"' | b'\'' => pos = skip_quoted_string(code, pos)?,
+            b'[' => {
+                pos = match long_bracket_level(code, pos) {
+                    Some(level) => skip_long_bracket(code, pos, level)?,
+                    None => pos + 1,
+                };
+            }
+            c if is_identifier_start(c) => {
+                let end = skip_identifier(code, pos);
+                let identifier = &code[pos..end];
+                if FORBIDDEN_IDENTIFIERS
+                    .iter()
+                    .any(|name| name.as_bytes() == identifier)
+                {
+                    return Err(Error::LuaForbiddenApi);
+                }
+                pos = if identifier == b"ckb" {
+                    check_ckb_field(code, end)?
+                } else {
+                    end
+                };
+            }
+            // skip numbers entirely, to not mistake exponent like `1e5` as an identifier
+            c if c.is_ascii_digit() => pos = skip_identifier(code, pos),
+            _ => pos += 1,
+        }
+    }
+    Ok(())
+}
+
+fn check_ckb_field(code: &[u8], pos: usize) -> Result<usize, Error> {
+    let pos = skip_whitespace(code, pos);
+    if code.get(pos) != Some(&b'.') {
+        return Err(Error::LuaForbiddenApi);
+    }
+    let start = skip_whitespace(code, pos + 1);
+    let end = skip_identifier(code, start);
+    let Ok(name) = core::str::from_utf8(&code[start..end]) else {
+        return Err(Error::LuaForbiddenApi);
+    };
+    let is_constant = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_');
+    if is_constant
+        || CKB_API_WHITELIST.contains(&name)
+        || CKB_API_WHITELIST_PREFIXES
+            .iter()
+            .any(|prefix| name.len() > prefix.len() && name.starts_with(prefix))
+    {
+        Ok(end)
+    } else {
+        Err(Error::LuaForbiddenApi)
+    }
+}
+
+fn is_identifier_start(c: u8) -> bool {
+    c.is_ascii_alphabetic() || c == b'_'
+}
+
+fn skip_identifier(code: &[u8], pos: usize) -> usize {
+    pos + code[pos..]
+        .iter()
+        .take_while(|&&c| c.is_ascii_alphanumeric() || c == b'_')
+        .count()
+}
+
+fn skip_whitespace(code: &[u8], pos: usize) -> usize {
+    pos + code[pos..]
+        .iter()
+        .take_while(|c| c.is_ascii_whitespace())
+        .count()
+}
+
+fn skip_line(code: &[u8], pos: usize) -> usize {
+    pos + code[pos..].iter().take_while(|&&c| c != b'\n').count()
+}
+
+fn skip_quoted_string(code: &[u8], pos: usize) -> Result<usize, Error> {
+    let quote = code[pos];
+    let mut pos = pos + 1;
+    while pos < code.len() {
+        match code[pos] {
+            b'\\' => pos += 2,
+            c if c == quote => return Ok(pos + 1),
+            _ => pos += 1,
+        }
+    }
+    Err(Error::LuaCompileFailed)
+}
+
+// returns the level of long bracket like `[==[` which starts at `pos`
+fn long_bracket_level(code: &[u8], pos: usize) -> Option<usize> {
+    if code.get(pos) != Some(&b'[') {
+        return None;
+    }
+    let level = code[pos + 1..].iter().take_while(|&&c| c == b'=').count();
+    (code.get(pos + 1 + level) == Some(&b'[')).then_some(level)
+}
+
+fn skip_long_bracket(code: &[u8], pos: usize, level: usize) -> Result<usize, Error> {
+    let mut pos = pos + level + 2;
+    while pos < code.len() {
+        if code[pos] == b']' {
+            let equals = code[pos + 1..].iter().take_while(|&&c| c == b'=').count();
+            if equals == level && code.get(pos + 1 + level) == Some(&b']') {
+                return Ok(pos + level + 2);
+            }
+        }
+        pos += 1;
+    }
+    Err(Error::LuaCompileFailed)
+}
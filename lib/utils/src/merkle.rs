@@ -0,0 +1,97 @@
+use alloc::vec::Vec;
+use ckb_std::ckb_types::util::hash::Blake2bBuilder;
+
+pub const ALLOWLIST_ROOT_LEN: usize = 32;
+
+const LEAF_PREFIX: u8 = 0;
+const NODE_PREFIX: u8 = 1;
+
+fn blake2b_with_prefix(prefix: u8, parts: &[&[u8]]) -> [u8; 32] {
+    let mut blake2b = Blake2bBuilder::new(32)
+        .personal(b"ckb-default-hash")
+        .build();
+    blake2b.update(&[prefix]);
+    parts.iter().for_each(|part| blake2b.update(part));
+    let mut hash = [0u8; 32];
+    blake2b.finalize(&mut hash);
+    hash
+}
+
+/// Leaf node of the allowlist tree, different prefixes prevent leaves from being taken as nodes
+pub fn hash_leaf(lock_hash: &[u8; 32]) -> [u8; 32] {
+    blake2b_with_prefix(LEAF_PREFIX, &[lock_hash])
+}
+
+/// Siblings are sorted before hashing, so proofs don't need to carry left/right directions
+pub fn hash_pair(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    if left <= right {
+        blake2b_with_prefix(NODE_PREFIX, &[left, right])
+    } else {
+        blake2b_with_prefix(NODE_PREFIX, &[right, left])
+    }
+}
+
+pub fn verify_allowlist_proof(root: &[u8; 32], lock_hash: &[u8; 32], proof: &[[u8; 32]]) -> bool {
+    let computed_root = proof.iter().fold(hash_leaf(lock_hash), |node, sibling| {
+        hash_pair(&node, sibling)
+    });
+    &computed_root == root
+}
+
+/// Host-side builder of allowlist tree, lock hashes are sorted and deduplicated, and the last
+/// node of an odd layer is promoted to the upper layer directly
+pub struct AllowlistTree {
+    lock_hashes: Vec<[u8; 32]>,
+    layers: Vec<Vec<[u8; 32]>>,
+}
+
+impl AllowlistTree {
+    pub fn new(lock_hashes: &[[u8; 32]]) -> Self {
+        let mut lock_hashes = lock_hashes.to_vec();
+        lock_hashes.sort();
+        lock_hashes.dedup();
+
+        let mut layers = Vec::new();
+        let mut layer: Vec<[u8; 32]> = lock_hashes.iter().map(hash_leaf).collect();
+        while layer.len() > 1 {
+            let upper_layer = layer
+                .chunks(2)
+                .map(|pair| match pair {
+                    [left, right] => hash_pair(left, right),
+                    [single] => *single,
+                    _ => unreachable!(),
+                })
+                .collect();
+            layers.push(layer);
+            layer = upper_layer;
+        }
+        layers.push(layer);
+
+        Self {
+            lock_hashes,
+            layers,
+        }
+    }
+
+    /// Root of an empty allowlist is all-zero, which no proof can match
+    pub fn root(&self) -> [u8; 32] {
+        self.layers
+            .last()
+            .and_then(|layer| layer.first())
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    pub fn proof(&self, lock_hash: &[u8; 32]) -> Option<Vec<[u8; 32]>> {
+        let mut index = self.lock_hashes.binary_search(lock_hash).ok()?;
+        let mut proof = Vec::new();
+        for layer in &self.layers[..self.layers.len() - 1] {
+            let sibling = index ^ 1;
+            if sibling < layer.len() {
+                proof.push(layer[sibling]);
+            }
+            index /= 2;
+        }
+        Some(proof)
+    }
+}
@@ -1,7 +1,15 @@
-// note: impelemtation refers to https://github.com/hyperium/mime
+// note: implementation follows the media type grammar of RFC 2045 and RFC 6838, parameters are
+// separated with optional whitespaces as RFC 9110 does, which is:
+//
+//   content-type = type "/" subtype *( OWS ";" OWS [ parameter ] )
+//   parameter    = attribute "=" ( token / quoted-string )
+//
+// type, subtype and attribute are restricted names of RFC 6838 and compared case-insensitively,
+// except `mutant[]` and `mutant_args[]` which are spore specific attributes
 
 use core::ops::Range;
 
+use alloc::borrow::Cow;
 use alloc::ffi::CString;
 use alloc::str;
 use alloc::vec::Vec;
@@ -9,104 +17,242 @@ use ckb_std::debug;
 use ckb_std::high_level::decode_hex;
 use spore_errors::error::Error;
 
+use crate::ContentEncoding;
+
 type RangePair = core::ops::Range<usize>;
 
-#[derive(Debug, Clone)]
-enum ParamType {
-    Generic(RangePair),
-    Immortal(RangePair),
-    Mutant(RangePair),
-}
+// RFC 6838 limits restricted names to 127 characters
+const MAX_RESTRICTED_NAME_LEN: usize = 127;
+
+const PARAM_IMMORTAL: &str = "immortal";
+const PARAM_STRICT: &str = "strict";
+const PARAM_ENCODING: &str = "encoding";
+const PARAM_MUTANT: &str = "mutant[]";
+const PARAM_MUTANT_ARGS: &str = "mutant_args[]";
 
 #[derive(Debug, Clone)]
 pub struct MIME {
     pub main_type: RangePair,
     pub sub_type: RangePair,
     pub mutants: Vec<[u8; 32]>,
+    pub mutant_versions: Vec<Option<u64>>,
+    pub mutant_args: Vec<RangePair>,
     pub immortal: bool,
+    pub strict: bool,
+    pub encoding: Option<ContentEncoding>,
     params: Vec<(RangePair, RangePair)>,
 }
 
 impl MIME {
     pub fn parse(raw_content_type: &[u8]) -> Result<MIME, Error> {
+        // leading whitespaces are rejected by the grammar rather than trimmed, since ranges of the
+        // parsed MIME index the raw content type, trailing ones are harmless to trim
         let content_type = match str::from_utf8(raw_content_type) {
             Ok(x) => x,
             _ => return Err(Error::Illformed),
         }
-        .trim_start()
         .trim_end();
         Self::str_parse(&content_type)
     }
 
     pub fn str_parse(content_type: &str) -> Result<Self, Error> {
         debug!("Content type is: {}", content_type);
-        // main_type.len() + '/' + sub_type.len() + '+' +
-        let (main_type, right) = match content_type.find('/') {
-            Some(pos) => (0usize..pos, pos..content_type.len()),
+        let bytes = content_type.as_bytes();
+        let main_type = match content_type.find('/') {
+            Some(pos) => 0usize..pos,
             _ => return Err(Error::Illformed),
         };
-
         if !is_restricted_name(&content_type[main_type.clone()]) {
             return Err(Error::InvaliMainType);
         }
 
-        if !content_type[right.clone()].chars().any(is_restricted_char) {
-            return Err(Error::Illformed);
+        let sub_start = main_type.end + 1;
+        let sub_end = sub_start + count_while(&bytes[sub_start..], is_restricted_name_char);
+        let sub_type = sub_start..sub_end;
+        if !is_restricted_name(&content_type[sub_type.clone()]) {
+            return Err(Error::InvalidSubType);
         }
 
-        let sub_end = content_type[right.clone()].find(';').unwrap_or(right.len()) + main_type.end;
-        let sub_type = main_type.end + 1..sub_end;
-
-        let mut vec = Vec::new();
-        let _right_part = &content_type[sub_end..];
+        let mut params: Vec<(RangePair, RangePair)> = Vec::new();
         let mut offset = sub_end;
-        let mut mutants = Vec::new();
-        let mut immortal = false;
-        while let Some((name_range, value_range, new_offset)) = parse_param(content_type, offset)? {
-            match name_range {
-                ParamType::Mutant(name_range) => {
-                    vec.push((name_range, value_range.clone()));
-                    let value = &content_type[value_range];
-                    for mutant_id in value.split(',') {
-                        // hexed mutant id doesn't have a prefix '0x'
-                        let mutant_id_hex = mutant_id.trim_matches(is_ows);
-                        if mutant_id_hex.len() != 64 {
-                            return Err(Error::MutantIDNotValid);
-                        }
-                        let mutant_id_c_str =
-                            CString::new(mutant_id_hex).map_err(|_| Error::MutantIDNotValid)?;
-                        let mutant_id: [u8; 32] = decode_hex(mutant_id_c_str.as_c_str())
-                            .map_err(|_| Error::MutantIDNotValid)?
-                            .try_into()
-                            .unwrap();
-                        if mutants.contains(&mutant_id) {
-                            return Err(Error::DuplicateMutantId);
-                        }
-                        mutants.push(mutant_id);
-                    }
-                }
-                ParamType::Generic(name_range) => {
-                    vec.push((name_range, value_range));
-                }
-                ParamType::Immortal(name_range) => {
-                    immortal = &content_type[value_range.clone()] == "true";
-                    vec.push((name_range, value_range));
-                }
+        loop {
+            offset = skip_ows(bytes, offset);
+            match bytes.get(offset) {
+                None => break,
+                Some(b';') => offset = skip_ows(bytes, offset + 1),
+                Some(_) if offset == sub_end => return Err(Error::InvalidSubType),
+                Some(_) => return Err(Error::InvalidParams),
+            }
+            // empty parameter, e.g. "image/png;;" or a trailing ';'
+            if matches!(bytes.get(offset), None | Some(b';')) {
+                continue;
+            }
+            let (name, value, end) = parse_param(content_type, offset)?;
+            if params.iter().any(|(exist, _)| {
+                content_type[exist.clone()].eq_ignore_ascii_case(&content_type[name.clone()])
+            }) {
+                return Err(Error::DuplicateParam);
             }
-            offset = new_offset;
+            params.push((name, value));
+            offset = end;
         }
 
-        let mime_type = MIME {
-            main_type: main_type,
-            sub_type: sub_type,
-            params: vec,
-            mutants,
-            immortal,
+        let mut mime_type = MIME {
+            main_type,
+            sub_type,
+            params: Vec::new(),
+            mutants: Vec::new(),
+            mutant_versions: Vec::new(),
+            mutant_args: Vec::new(),
+            immortal: false,
+            strict: false,
+            encoding: None,
         };
+        for (name, value) in params.iter() {
+            let name = &content_type[name.clone()];
+            if name.eq_ignore_ascii_case(PARAM_IMMORTAL) {
+                mime_type.immortal = Self::unquote(bytes, value)?.as_ref() == b"true";
+            } else if name.eq_ignore_ascii_case(PARAM_STRICT) {
+                mime_type.strict = Self::unquote(bytes, value)?.as_ref() == b"true";
+            } else if name.eq_ignore_ascii_case(PARAM_ENCODING) {
+                let encoding = ContentEncoding::from_name(&Self::unquote(bytes, value)?)
+                    .ok_or(Error::UnsupportedContentEncoding)?;
+                mime_type.encoding = Some(encoding);
+            } else if name.eq_ignore_ascii_case(PARAM_MUTANT) {
+                mime_type.parse_mutants(&Self::unquote(bytes, value)?)?;
+            } else if name.eq_ignore_ascii_case(PARAM_MUTANT_ARGS) {
+                mime_type.parse_mutant_args(content_type, value)?;
+            }
+        }
+        mime_type.params = params;
 
+        if !mime_type.mutant_args.is_empty()
+            && mime_type.mutant_args.len() != mime_type.mutants.len()
+        {
+            return Err(Error::MutantArgsNotMatch);
+        }
         Ok(mime_type)
     }
 
+    /// Parses content type of an existing spore, which may be minted under a looser grammar, e.g.
+    /// with leading whitespaces. Malformed parts are skipped instead of rejected, only mutants and
+    /// their args are still checked, so transfer and burn never fail on a content type that was
+    /// accepted by mint. Content types of the strict grammar are parsed the same as `parse` does
+    pub fn parse_lenient(raw_content_type: &[u8]) -> Result<MIME, Error> {
+        let content_type = str::from_utf8(raw_content_type).map_err(|_| Error::Illformed)?;
+        let bytes = content_type.as_bytes();
+        let mut segments = split_params(bytes).into_iter();
+        let full_type = segments.next().unwrap_or_default();
+        let (main_type, sub_type) = match content_type[full_type.clone()].find('/') {
+            Some(pos) => (
+                trim_range(bytes, full_type.start..full_type.start + pos),
+                trim_range(bytes, full_type.start + pos + 1..full_type.end),
+            ),
+            None => (
+                trim_range(bytes, full_type.clone()),
+                full_type.end..full_type.end,
+            ),
+        };
+
+        let mut mime_type = MIME {
+            main_type,
+            sub_type,
+            params: Vec::new(),
+            mutants: Vec::new(),
+            mutant_versions: Vec::new(),
+            mutant_args: Vec::new(),
+            immortal: false,
+            strict: false,
+            encoding: None,
+        };
+        for segment in segments {
+            let Some(pos) = content_type[segment.clone()].find('=') else {
+                continue;
+            };
+            let name_range = trim_range(bytes, segment.start..segment.start + pos);
+            let value = trim_range(bytes, segment.start + pos + 1..segment.end);
+            // an unterminated quoted value can't be unquoted
+            if bytes[value.clone()].starts_with(b"\"")
+                && (value.len() < 2 || !bytes[value.clone()].ends_with(b"\""))
+            {
+                continue;
+            }
+            let name = &content_type[name_range.clone()];
+            if name.eq_ignore_ascii_case(PARAM_IMMORTAL) {
+                mime_type.immortal = Self::unquote(bytes, &value)?.as_ref() == b"true";
+            } else if name.eq_ignore_ascii_case(PARAM_STRICT) {
+                mime_type.strict = Self::unquote(bytes, &value)?.as_ref() == b"true";
+            } else if name.eq_ignore_ascii_case(PARAM_ENCODING) {
+                mime_type.encoding = ContentEncoding::from_name(&Self::unquote(bytes, &value)?);
+            } else if name.eq_ignore_ascii_case(PARAM_MUTANT) {
+                mime_type.parse_mutants(&Self::unquote(bytes, &value)?)?;
+            } else if name.eq_ignore_ascii_case(PARAM_MUTANT_ARGS) {
+                mime_type.parse_mutant_args(content_type, &value)?;
+            }
+            mime_type.params.push((name_range, value));
+        }
+
+        if !mime_type.mutant_args.is_empty()
+            && mime_type.mutant_args.len() != mime_type.mutants.len()
+        {
+            return Err(Error::MutantArgsNotMatch);
+        }
+        Ok(mime_type)
+    }
+
+    fn parse_mutants(&mut self, value: &[u8]) -> Result<(), Error> {
+        let value = str::from_utf8(value).map_err(|_| Error::MutantIDNotValid)?;
+        for mutant_id in value.split(',') {
+            // mutant id can be pinned to a version with '@', e.g. "MUTANT_ID@2",
+            // which requires a quoted value
+            let (mutant_id, version) = match mutant_id.split_once('@') {
+                Some((mutant_id, version)) => {
+                    let version = version
+                        .trim_matches(is_ows)
+                        .parse::<u64>()
+                        .map_err(|_| Error::MutantVersionNotValid)?;
+                    (mutant_id, Some(version))
+                }
+                None => (mutant_id, None),
+            };
+            // hexed mutant id doesn't have a prefix '0x'
+            let mutant_id_hex = mutant_id.trim_matches(is_ows);
+            if mutant_id_hex.len() != 64 {
+                return Err(Error::MutantIDNotValid);
+            }
+            let mutant_id_c_str =
+                CString::new(mutant_id_hex).map_err(|_| Error::MutantIDNotValid)?;
+            let mutant_id: [u8; 32] = decode_hex(mutant_id_c_str.as_c_str())
+                .map_err(|_| Error::MutantIDNotValid)?
+                .try_into()
+                .unwrap();
+            if self.mutants.contains(&mutant_id) {
+                return Err(Error::DuplicateMutantId);
+            }
+            self.mutants.push(mutant_id);
+            self.mutant_versions.push(version);
+        }
+        Ok(())
+    }
+
+    // args are split by ',' in the same order of mutant ids, and referred by ranges of content type,
+    // so quoted-pairs are not allowed in them
+    fn parse_mutant_args(&mut self, content_type: &str, value: &RangePair) -> Result<(), Error> {
+        let value_range = unquoted_range(content_type.as_bytes(), value);
+        if content_type[value_range.clone()].contains('\\') {
+            return Err(Error::InvalidParamValue);
+        }
+        let mut cursor = value_range.start;
+        for arg in content_type[value_range].split(',') {
+            let leading = arg.len() - arg.trim_start_matches(is_ows).len();
+            let start = cursor + leading;
+            self.mutant_args
+                .push(start..start + arg.trim_matches(is_ows).len());
+            cursor += arg.len() + 1;
+        }
+        Ok(())
+    }
+
     pub fn params(&self) -> &Vec<(RangePair, RangePair)> {
         &self.params
     }
@@ -115,16 +261,52 @@ impl MIME {
         &mut self.params
     }
 
+    /// Returns the raw value range of a parameter, quotes included, the name is case-insensitive
     pub fn get_param(&self, content_type: &[u8], param: &str) -> Result<Option<RangePair>, Error> {
         for (param_range, value_range) in self.params.iter() {
             check_range_validate(content_type, param_range)?;
-            if content_type[param_range.clone()] == param.as_bytes()[..] {
+            if content_type[param_range.clone()].eq_ignore_ascii_case(param.as_bytes()) {
                 check_range_validate(content_type, value_range)?;
                 return Ok(Some(value_range.clone()));
             }
         }
         Ok(None)
     }
+
+    /// Returns the value of a parameter with quotes removed and quoted-pairs unescaped
+    pub fn get_param_value<'a>(
+        &self,
+        content_type: &'a [u8],
+        param: &str,
+    ) -> Result<Option<Cow<'a, [u8]>>, Error> {
+        match self.get_param(content_type, param)? {
+            Some(value_range) => Ok(Some(Self::unquote(content_type, &value_range)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Removes quotes of a parameter value and unescapes quoted-pairs in it, e.g. `"a\"b"` to `a"b`
+    pub fn unquote<'a>(
+        content_type: &'a [u8],
+        value_range: &RangePair,
+    ) -> Result<Cow<'a, [u8]>, Error> {
+        check_range_validate(content_type, value_range)?;
+        let value = &content_type[unquoted_range(content_type, value_range)];
+        if !value.contains(&b'\\') {
+            return Ok(Cow::Borrowed(value));
+        }
+        let mut unescaped = Vec::with_capacity(value.len());
+        let mut escaped = false;
+        for &byte in value {
+            if byte == b'\\' && !escaped {
+                escaped = true;
+                continue;
+            }
+            escaped = false;
+            unescaped.push(byte);
+        }
+        Ok(Cow::Owned(unescaped))
+    }
 }
 
 fn check_range_validate(array: &[u8], range: &Range<usize>) -> Result<(), Error> {
@@ -136,103 +318,155 @@ fn check_range_validate(array: &[u8], range: &Range<usize>) -> Result<(), Error>
     Ok(())
 }
 
-fn is_restricted_name(s: &str) -> bool {
-    s.starts_with(|c: char| c.is_ascii_alphanumeric() || c == '*') && is_restricted_str(s)
+fn unquoted_range(content_type: &[u8], range: &RangePair) -> RangePair {
+    if content_type[range.clone()].starts_with(b"\"") {
+        range.start + 1..range.end - 1
+    } else {
+        range.clone()
+    }
+}
+
+// splits content type by ';' outside of quoted-strings
+fn split_params(bytes: &[u8]) -> Vec<RangePair> {
+    let mut segments = Vec::new();
+    let mut start = 0;
+    let mut quoted = false;
+    let mut escaped = false;
+    for (i, &byte) in bytes.iter().enumerate() {
+        match byte {
+            _ if escaped => escaped = false,
+            b'\\' if quoted => escaped = true,
+            b'"' => quoted = !quoted,
+            b';' if !quoted => {
+                segments.push(start..i);
+                start = i + 1;
+            }
+            _ => (),
+        }
+    }
+    segments.push(start..bytes.len());
+    segments
 }
 
-fn is_restricted_name_patched(s: &str) -> bool {
-    s == "mutant[]" || is_restricted_name(s)
+// shrinks range to exclude surrounding whitespaces
+fn trim_range(bytes: &[u8], range: RangePair) -> RangePair {
+    let value = &bytes[range.clone()];
+    let start = range.start + count_while(value, |byte| byte.is_ascii_whitespace());
+    let end = range.end
+        - value
+            .iter()
+            .rev()
+            .take_while(|byte| byte.is_ascii_whitespace())
+            .count();
+    start..end.max(start)
 }
 
-fn is_restricted_value_char(c: char) -> bool {
-    c.is_ascii_alphanumeric()
-        || matches!(
-            c,
-            '!' | '#' | '$' | '&' | '-' | '^' | '_' | '.' | '+' | '%' | '*' | '\'' | ','
-        )
+fn count_while(bytes: &[u8], predicate: fn(u8) -> bool) -> usize {
+    bytes.iter().take_while(|&&byte| predicate(byte)).count()
 }
 
-fn is_restricted_str(s: &str) -> bool {
-    s.chars().all(is_restricted_char)
+fn skip_ows(bytes: &[u8], offset: usize) -> usize {
+    offset + count_while(&bytes[offset..], |byte| is_ows(byte as char))
 }
 
-fn is_restricted_char(c: char) -> bool {
-    c.is_ascii_alphanumeric()
+// restricted-name = restricted-name-first *126restricted-name-chars
+fn is_restricted_name(s: &str) -> bool {
+    s.len() <= MAX_RESTRICTED_NAME_LEN
+        && s.starts_with(|c: char| c.is_ascii_alphanumeric())
+        && s.bytes().all(is_restricted_name_char)
+}
+
+fn is_restricted_name_char(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric()
         || matches!(
-            c,
-            '!' | '#' | '$' | '&' | '-' | '^' | '_' | '.' | '+' | '%' | '*' | '\''
+            byte,
+            b'!' | b'#' | b'$' | b'&' | b'-' | b'^' | b'_' | b'.' | b'+'
         )
 }
 
-const fn is_ows(c: char) -> bool {
-    c == ' ' || c == '\t'
+// any printable ASCII except space and tspecials of RFC 2045
+pub(crate) fn is_token_char(byte: u8) -> bool {
+    byte.is_ascii_graphic()
+        && !matches!(
+            byte,
+            b'(' | b')'
+                | b'<'
+                | b'>'
+                | b'@'
+                | b','
+                | b';'
+                | b':'
+                | b'\\'
+                | b'"'
+                | b'/'
+                | b'['
+                | b']'
+                | b'?'
+                | b'='
+        )
 }
 
-fn parse_param(
-    source: &str,
-    offset: usize,
-) -> Result<Option<(ParamType, RangePair, usize)>, Error> {
-    if offset >= source.len() {
-        return Ok(None);
-    }
-    let s = &source[offset..];
-    let (lhs, rhs) = match s.split_once(';') {
-        Some((lhs, rhs)) if lhs.chars().all(is_ows) && rhs.chars().all(is_ows) => return Ok(None),
-        Some((lhs, rhs)) if lhs.chars().all(is_ows) => (lhs, rhs),
-        _ if s.chars().all(is_ows) => return Ok(None),
-        _ => return Err(Error::InvalidParams),
-    };
+// mutant params are lists separated by ',', which is allowed in their tokens for compatibility
+fn is_list_token_char(byte: u8) -> bool {
+    byte == b',' || is_token_char(byte)
+}
 
-    let (name, value) = match rhs.split_once('=') {
-        Some((name, value_maybe)) => match value_maybe.split_once(';') {
-            None => (name, value_maybe),
-            Some((value_maybe_lhs, _)) => (name, value_maybe_lhs),
-        },
-        _ => return Err(Error::InvalidParams),
-    };
+const fn is_ows(c: char) -> bool {
+    c == ' ' || c == '\t'
+}
 
-    let key_trimmed = name.trim_start_matches(is_ows).len();
-    let key_start = lhs.len() + 1 + name.len() - key_trimmed;
-    let key_range = key_start + offset..key_start + offset + key_trimmed;
-    if !is_restricted_name_patched(&source[key_range.clone()]) {
+// parses `attribute "=" value` at offset, returns ranges of attribute and value (quotes included)
+// and the end of the parameter
+fn parse_param(source: &str, offset: usize) -> Result<(RangePair, RangePair, usize), Error> {
+    let bytes = source.as_bytes();
+    let mut name_end = offset + count_while(&bytes[offset..], is_restricted_name_char);
+    if bytes[name_end..].starts_with(b"[]") {
+        name_end += 2;
+    }
+    let name_range = offset..name_end;
+    let name = &source[name_range.clone()];
+    let is_list =
+        name.eq_ignore_ascii_case(PARAM_MUTANT) || name.eq_ignore_ascii_case(PARAM_MUTANT_ARGS);
+    if !is_list && !is_restricted_name(name) {
         return Err(Error::InvalidParams);
     }
-    let key = match &source[key_range.clone()] {
-        "immortal" => ParamType::Immortal(key_range.clone()),
-        "mutant[]" => ParamType::Mutant(key_range.clone()),
-        _ => ParamType::Generic(key_range.clone()),
-    };
-    let value_start = key_range.end + 1;
-    if let Some(value) = value.strip_prefix('\"') {
-        let value_end = value_start + parse_quoted_value(value)? + 1;
-        let value_range = value_start..value_end;
-        Ok(Some((key.clone(), value_range.clone(), value_end)))
-    } else {
-        let value_end = value_start
-            + value
-                .chars()
-                .take_while(|&c| is_restricted_value_char(c))
-                .map(char::len_utf8)
-                .sum::<usize>();
-        let value_range = value_start..value_end;
-        Ok(Some((key.clone(), value_range.clone(), value_end)))
+    if bytes.get(name_end) != Some(&b'=') {
+        return Err(Error::InvalidParams);
     }
+
+    let value_start = name_end + 1;
+    let value_end = if bytes.get(value_start) == Some(&b'"') {
+        value_start + 1 + parse_quoted_value(&bytes[value_start + 1..])?
+    } else {
+        let token_char = if is_list {
+            is_list_token_char
+        } else {
+            is_token_char
+        };
+        let len = count_while(&bytes[value_start..], token_char);
+        if len == 0 {
+            return Err(Error::InvalidParamValue);
+        }
+        value_start + len
+    };
+    Ok((name_range, value_start..value_end, value_end))
 }
 
-fn parse_quoted_value(s: &str) -> Result<usize, Error> {
-    let mut len = 0;
+// returns the length of quoted-string content after the opening quote, closing quote included
+//
+//   quoted-string = DQUOTE *( qdtext / quoted-pair ) DQUOTE
+//   quoted-pair   = "\" ( HTAB / SP / VCHAR / obs-text )
+fn parse_quoted_value(bytes: &[u8]) -> Result<usize, Error> {
     let mut escaped = false;
-    for c in s.chars() {
-        len += c.len_utf8();
-        match c {
-            _ if escaped => {
-                escaped = false;
-            }
-            '\\' => {
-                escaped = true;
-            }
-            '"' => return Ok(len),
-            '\n' => return Err(Error::InvalidParamValue),
+    for (i, &byte) in bytes.iter().enumerate() {
+        // control characters are not allowed even if escaped, except HTAB
+        if byte.is_ascii_control() && byte != b'\t' {
+            return Err(Error::InvalidParamValue);
+        }
+        match byte {
+            _ if escaped => escaped = false,
+            b'\\' => escaped = true,
+            b'"' => return Ok(i + 1),
             _ => (),
         }
     }
@@ -0,0 +1,207 @@
+// Owned companion of `MIME` for off-chain usage, e.g. SDKs building content types of spores
+
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt::{self, Write};
+use core::str::FromStr;
+
+use spore_errors::error::Error;
+
+use crate::mime::{is_token_char, MIME};
+
+/// A mutant applied by content type, i.e. an item of `mutant[]` and `mutant_args[]`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MutantRef {
+    pub id: [u8; 32],
+    pub version: Option<u64>,
+    pub args: Option<String>,
+}
+
+/// Owned content type, which serializes to the canonical form accepted by spore contract:
+/// lowercase names, generic params in order, then `immortal`, `mutant[]` and `mutant_args[]`
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Mime {
+    pub main_type: String,
+    pub sub_type: String,
+    pub params: Vec<(String, String)>,
+    pub mutants: Vec<MutantRef>,
+    pub immortal: bool,
+}
+
+impl Mime {
+    pub fn new(main_type: &str, sub_type: &str) -> Self {
+        Self {
+            main_type: main_type.to_ascii_lowercase(),
+            sub_type: sub_type.to_ascii_lowercase(),
+            ..Default::default()
+        }
+    }
+
+    pub fn param(mut self, name: &str, value: &str) -> Self {
+        self.params
+            .push((name.to_ascii_lowercase(), value.to_string()));
+        self
+    }
+
+    pub fn mutant(mut self, id: [u8; 32]) -> Self {
+        self.mutants.push(MutantRef {
+            id,
+            version: None,
+            args: None,
+        });
+        self
+    }
+
+    pub fn pinned_mutant(mut self, id: [u8; 32], version: u64) -> Self {
+        self.mutants.push(MutantRef {
+            id,
+            version: Some(version),
+            args: None,
+        });
+        self
+    }
+
+    /// Sets args of the last added mutant, which can't contain `,`, `"` or `\`
+    pub fn mutant_args(mut self, args: &str) -> Self {
+        if let Some(mutant) = self.mutants.last_mut() {
+            mutant.args = Some(args.to_string());
+        }
+        self
+    }
+
+    pub fn immortal(mut self, immortal: bool) -> Self {
+        self.immortal = immortal;
+        self
+    }
+
+    pub fn get_param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(param, _)| param.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Parses raw content type with `MIME`, so the same content types are accepted as in contracts
+    pub fn parse(raw_content_type: &[u8]) -> Result<Self, Error> {
+        let mime = MIME::parse(raw_content_type)?;
+        let content_type = core::str::from_utf8(raw_content_type)
+            .map_err(|_| Error::Illformed)?
+            .trim();
+        let mut owned = Self::new(
+            &content_type[mime.main_type.clone()],
+            &content_type[mime.sub_type.clone()],
+        )
+        .immortal(mime.immortal);
+        owned.mutants = mime
+            .mutants
+            .iter()
+            .zip(&mime.mutant_versions)
+            .enumerate()
+            .map(|(i, (id, version))| MutantRef {
+                id: *id,
+                version: *version,
+                args: mime
+                    .mutant_args
+                    .get(i)
+                    .map(|range| content_type[range.clone()].to_string()),
+            })
+            .collect();
+        for (name, value) in mime.params() {
+            let name = content_type[name.clone()].to_ascii_lowercase();
+            if is_spore_param(&name) {
+                continue;
+            }
+            let value = MIME::unquote(content_type.as_bytes(), value)?;
+            let value =
+                String::from_utf8(value.into_owned()).map_err(|_| Error::InvalidParamValue)?;
+            owned.params.push((name, value));
+        }
+        Ok(owned)
+    }
+
+    /// Serializes to the canonical form and makes sure it's accepted by `MIME`
+    pub fn to_bytes(&self) -> Result<Vec<u8>, Error> {
+        let content_type = self.to_string();
+        MIME::str_parse(&content_type)?;
+        Ok(content_type.into_bytes())
+    }
+}
+
+impl fmt::Display for Mime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.main_type, self.sub_type)?;
+        for (name, value) in &self.params {
+            write!(f, ";{name}={}", quote_if_needed(value))?;
+        }
+        if self.immortal {
+            write!(f, ";immortal=true")?;
+        }
+        if self.mutants.is_empty() {
+            return Ok(());
+        }
+        let mutants = self
+            .mutants
+            .iter()
+            .map(|mutant| match mutant.version {
+                Some(version) => format!("{}@{version}", to_hex(&mutant.id)),
+                None => to_hex(&mutant.id),
+            })
+            .collect::<Vec<_>>()
+            .join(",");
+        // versions are only allowed in quoted value
+        if self.mutants.iter().any(|mutant| mutant.version.is_some()) {
+            write!(f, ";mutant[]=\"{mutants}\"")?;
+        } else {
+            write!(f, ";mutant[]={mutants}")?;
+        }
+        if self.mutants.iter().any(|mutant| mutant.args.is_some()) {
+            let args = self
+                .mutants
+                .iter()
+                .map(|mutant| mutant.args.as_deref().unwrap_or_default())
+                .collect::<Vec<_>>()
+                .join(",");
+            write!(f, ";mutant_args[]=\"{args}\"")?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Mime {
+    type Err = Error;
+
+    fn from_str(content_type: &str) -> Result<Self, Self::Err> {
+        Self::parse(content_type.as_bytes())
+    }
+}
+
+fn is_spore_param(name: &str) -> bool {
+    matches!(name, "immortal" | "mutant[]" | "mutant_args[]")
+}
+
+fn quote_if_needed(value: &str) -> String {
+    let is_token = !value.is_empty() && value.bytes().all(is_token_char);
+    if is_token {
+        return value.to_string();
+    }
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for c in value.chars() {
+        if matches!(c, '"' | '\\') {
+            quoted.push('\\');
+        }
+        quoted.push(c);
+    }
+    quoted.push('"');
+    quoted
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .fold(String::with_capacity(bytes.len() * 2), |mut hex, byte| {
+            let _ = write!(hex, "{byte:02x}");
+            hex
+        })
+}
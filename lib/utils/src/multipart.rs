@@ -0,0 +1,213 @@
+// Multipart content of spore, refers to https://datatracker.ietf.org/doc/html/rfc2046#section-5.1
+//
+// note: lines are allowed to end with a bare LF besides CRLF, and nested multipart parts are only
+// validated by their content types
+
+use alloc::vec::Vec;
+use spore_errors::error::Error;
+
+use crate::{validate_content, MIME};
+
+/// Limit of parts in a multipart spore enforced by contract
+pub const MAX_MULTIPART_PARTS: usize = 64;
+
+// RFC 2046 limits boundary to 70 characters
+const MAX_BOUNDARY_LEN: usize = 70;
+
+/// A body part of multipart content, headers and body are slices of the content
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MultipartPart<'a> {
+    pub headers: &'a [u8],
+    /// value of the `Content-Type` header, `text/plain` is implied if absent
+    pub content_type: Option<&'a [u8]>,
+    pub body: &'a [u8],
+}
+
+/// Extracts parts from content of a multipart spore, e.g. for renderers
+pub fn extract_multipart_parts<'a>(
+    raw_content_type: &[u8],
+    content: &'a [u8],
+) -> Result<Vec<MultipartPart<'a>>, Error> {
+    let mime = MIME::parse(raw_content_type)?;
+    let content_type = core::str::from_utf8(raw_content_type)
+        .map_err(|_| Error::Illformed)?
+        .trim()
+        .as_bytes();
+    if !content_type[mime.main_type.clone()].eq_ignore_ascii_case(b"multipart") {
+        return Err(Error::InvalidContentType);
+    }
+    let boundary = mime
+        .get_param_value(content_type, "boundary")?
+        .ok_or(Error::InvalidContentType)?;
+    parse_multipart(content, &boundary, None)
+}
+
+/// Parses multipart content into parts, which requires at least one part and the close delimiter,
+/// and content type of each part must be valid and match its body
+pub fn parse_multipart<'a>(
+    content: &'a [u8],
+    boundary: &[u8],
+    max_parts: Option<usize>,
+) -> Result<Vec<MultipartPart<'a>>, Error> {
+    if !is_valid_boundary(boundary) {
+        return Err(Error::BoundaryEncoding);
+    }
+
+    let mut parts = Vec::new();
+    // start of the current part, none before the first delimiter, i.e. in preamble
+    let mut part_start = None;
+    let mut line_start = 0;
+    while line_start < content.len() {
+        let (line_end, next_line) = match content[line_start..].iter().position(|&b| b == b'\n') {
+            Some(pos) => (line_start + pos, line_start + pos + 1),
+            None => (content.len(), content.len()),
+        };
+        let line = strip_cr(&content[line_start..line_end]);
+        if let Some(delimiter) = line
+            .strip_prefix(b"--")
+            .and_then(|line| line.strip_prefix(boundary))
+        {
+            let is_close = delimiter.starts_with(b"--");
+            let padding = if is_close { &delimiter[2..] } else { delimiter };
+            // transport padding is allowed after delimiters
+            if padding.iter().all(|&b| b == b' ' || b == b'\t') {
+                if let Some(start) = part_start {
+                    // line break before the delimiter belongs to the delimiter
+                    let end = line_start - line_break_len(content, line_start);
+                    parts.push(parse_part(&content[start..end.max(start)])?);
+                    if matches!(max_parts, Some(max_parts) if parts.len() > max_parts) {
+                        return Err(Error::TooManyMultipartParts);
+                    }
+                }
+                if is_close {
+                    // epilogue is ignored
+                    return if parts.is_empty() {
+                        Err(Error::InvalidMultipartContent)
+                    } else {
+                        Ok(parts)
+                    };
+                }
+                part_start = Some(next_line);
+            }
+        }
+        line_start = next_line;
+    }
+    // close delimiter is missing
+    Err(Error::InvalidMultipartContent)
+}
+
+fn parse_part(part: &[u8]) -> Result<MultipartPart<'_>, Error> {
+    // headers end with an empty line, a part without headers starts with the empty line
+    let mut line_start = 0;
+    let mut headers = Headers::default();
+    let (raw_headers, body) = loop {
+        let Some(pos) = part[line_start..].iter().position(|&b| b == b'\n') else {
+            // a part with headers only
+            headers.push(strip_cr(&part[line_start..]))?;
+            break (part, &part[part.len()..]);
+        };
+        let line = strip_cr(&part[line_start..line_start + pos]);
+        let next_line = line_start + pos + 1;
+        if line.is_empty() {
+            break (&part[..line_start], &part[next_line..]);
+        }
+        headers.push(line)?;
+        line_start = next_line;
+    };
+    if let Some(content_type) = headers.content_type {
+        let mime = MIME::parse(content_type)?;
+        validate_content(content_type, &mime, body)?;
+    }
+    Ok(MultipartPart {
+        headers: raw_headers,
+        content_type: headers.content_type,
+        body,
+    })
+}
+
+#[derive(Default)]
+struct Headers<'a> {
+    content_type: Option<&'a [u8]>,
+    // whether the last header line is `Content-Type`, none if no header yet
+    last_is_content_type: Option<bool>,
+}
+
+impl<'a> Headers<'a> {
+    fn push(&mut self, line: &'a [u8]) -> Result<(), Error> {
+        if line.is_empty() {
+            return Ok(());
+        }
+        // folded lines continue the last header, which is unsupported for `Content-Type`
+        if line[0] == b' ' || line[0] == b'\t' {
+            return match self.last_is_content_type {
+                Some(false) => Ok(()),
+                _ => Err(Error::InvalidMultipartContent),
+            };
+        }
+        let colon = line
+            .iter()
+            .position(|&b| b == b':')
+            .ok_or(Error::InvalidMultipartContent)?;
+        let (name, value) = (&line[..colon], &line[colon + 1..]);
+        if name.is_empty() || !name.iter().all(|b| b.is_ascii_graphic()) {
+            return Err(Error::InvalidMultipartContent);
+        }
+        let is_content_type = name.eq_ignore_ascii_case(b"content-type");
+        if is_content_type {
+            if self.content_type.is_some() {
+                return Err(Error::InvalidMultipartContent);
+            }
+            self.content_type = Some(trim_ows(value));
+        }
+        self.last_is_content_type = Some(is_content_type);
+        Ok(())
+    }
+}
+
+// bchars := bcharsnospace / " ", which can't end with a space
+fn is_valid_boundary(boundary: &[u8]) -> bool {
+    (1..=MAX_BOUNDARY_LEN).contains(&boundary.len())
+        && !boundary.ends_with(b" ")
+        && boundary.iter().all(|&b| {
+            b.is_ascii_alphanumeric()
+                || matches!(
+                    b,
+                    b'\''
+                        | b'('
+                        | b')'
+                        | b'+'
+                        | b'_'
+                        | b','
+                        | b'-'
+                        | b'.'
+                        | b'/'
+                        | b':'
+                        | b'='
+                        | b'?'
+                        | b' '
+                )
+        })
+}
+
+fn strip_cr(line: &[u8]) -> &[u8] {
+    line.strip_suffix(b"\r").unwrap_or(line)
+}
+
+// length of the line break ending right before `line_start`
+fn line_break_len(content: &[u8], line_start: usize) -> usize {
+    match &content[..line_start] {
+        [.., b'\r', b'\n'] => 2,
+        [.., b'\n'] => 1,
+        _ => 0,
+    }
+}
+
+fn trim_ows(value: &[u8]) -> &[u8] {
+    let is_ows = |b: &u8| *b == b' ' || *b == b'\t';
+    let start = value.iter().position(|b| !is_ows(b)).unwrap_or(value.len());
+    let end = value
+        .iter()
+        .rposition(|b| !is_ows(b))
+        .map_or(start, |pos| pos + 1);
+    &value[start..end]
+}
@@ -1,18 +1,34 @@
-use ckb_testtool::ckb_types::{core::TransactionBuilder, prelude::*};
+use ckb_testtool::ckb_types::{core::TransactionBuilder, packed, prelude::*};
 use ckb_testtool::context::Context;
+use spore_types::generated::spore::ClusterDataV2;
+use spore_types::native::NativeClusterDataV2;
+use spore_utils::Mime;
 
+use crate::utils::co_build::*;
 use crate::utils::*;
 use crate::MAX_CYCLES;
 
-#[test]
-fn test_simple_mutant_mint() {
+const MUTANT_ENTRY_POINTS_CODE: &str = "
+    function on_mint() end
+    function on_transfer() end
+    function on_burn() end
+";
+
+fn make_simple_mutant_mint(lua_code: &str) {
+    make_mutant_mint_with_args_suffix(lua_code, &[]);
+}
+
+fn make_mutant_mint_with_args_suffix(lua_code: &str, args_suffix: &[u8]) {
+    make_mutant_mint_with_data(lua_code.as_bytes(), args_suffix);
+}
+
+fn make_mutant_mint_with_data(mutant_data: &[u8], args_suffix: &[u8]) {
     let mut context = Context::default();
 
     let (_, lua_lib_dep) = build_spore_contract_materials(&mut context, "libckblua.so");
     let (spore_extension_out_point, spore_extension_script_dep) =
         build_spore_contract_materials(&mut context, "spore_extension_lua");
 
-    let lua_code = String::from("print('hello world')");
     let input_cell = build_normal_input(&mut context);
 
     println!(
@@ -24,7 +40,9 @@ fn test_simple_mutant_mint() {
     let type_ = build_spore_type_script(
         &mut context,
         &spore_extension_out_point,
-        mutant_id.to_vec().into(),
+        vec![mutant_id.to_vec(), args_suffix.to_vec()]
+            .concat()
+            .into(),
     );
 
     let mutant_cell_output = build_normal_output_cell_with_type(&mut context, type_.clone());
@@ -32,7 +50,7 @@ fn test_simple_mutant_mint() {
     let tx = TransactionBuilder::default()
         .input(input_cell)
         .output(mutant_cell_output)
-        .output_data(lua_code.pack())
+        .output_data(mutant_data.pack())
         .cell_deps(vec![lua_lib_dep, spore_extension_script_dep])
         .build();
 
@@ -43,6 +61,208 @@ fn test_simple_mutant_mint() {
         .expect("test mint mutant_cell");
 }
 
+#[test]
+fn test_simple_mutant_mint() {
+    make_simple_mutant_mint(&format!("print('hello world')\n{MUTANT_ENTRY_POINTS_CODE}"));
+}
+
+#[should_panic]
+#[test]
+fn test_simple_mutant_mint_failed_with_syntax_error() {
+    make_simple_mutant_mint(&format!("print('hello world'\n{MUTANT_ENTRY_POINTS_CODE}"));
+}
+
+#[should_panic]
+#[test]
+fn test_simple_mutant_mint_failed_without_entry_points() {
+    make_simple_mutant_mint("print('hello world')");
+}
+
+#[should_panic]
+#[test]
+fn test_simple_mutant_mint_failed_with_forbidden_api() {
+    make_simple_mutant_mint(&format!("ckb.spawn_cell()\n{MUTANT_ENTRY_POINTS_CODE}"));
+}
+
+// a chunk closing the wrapper function would run outside of its sandbox
+#[should_panic]
+#[test]
+fn test_simple_mutant_mint_failed_with_unbalanced_code() {
+    make_simple_mutant_mint(&format!(
+        "end\n{MUTANT_ENTRY_POINTS_CODE}\nlocal function spore_ext_tail()"
+    ));
+}
+
+// examples of docs/MUTANT.md, which are created through validation
+#[test]
+fn test_documented_mutant_mint() {
+    make_simple_mutant_mint(
+        "
+        function on_mint()
+          print(\"Hello, world!\");
+          if spore_output_index > 0 then
+            ckb.exit_script(spore_output_index)
+          end
+        end
+
+        function on_transfer() end
+        function on_burn() end
+        ",
+    );
+}
+
+#[test]
+fn test_documented_mutant_mint_with_args() {
+    make_simple_mutant_mint(
+        "
+        function on_mint()
+          if spore_ext_args ~= \"edition-5\" then
+            ckb.exit_script(1)
+          end
+        end
+
+        function on_transfer() end
+        function on_burn() end
+        ",
+    );
+}
+
+#[test]
+fn test_documented_mutant_mint_with_spore_error() {
+    make_simple_mutant_mint(
+        "
+        function on_mint()
+          if spore_data.params[\"edition\"] == \"6\" then
+            spore_error(1)
+          end
+        end
+
+        function on_transfer() end
+        function on_burn() end
+        ",
+    );
+}
+
+#[test]
+fn test_documented_mutant_mint_with_pre_decoded_data() {
+    make_simple_mutant_mint(
+        "
+        local function check_edition()
+          if spore_data.sub_type ~= \"png\" or spore_data.params[\"edition\"] ~= \"5\" then
+            ckb.exit_script(1)
+          end
+        end
+
+        on_mint = check_edition
+        on_transfer = check_edition
+        function on_burn() end
+        ",
+    );
+}
+
+// external values are stubbed in validation, so reading them at the top level is fine
+#[test]
+fn test_mutant_mint_reading_external_values_at_top_level() {
+    make_simple_mutant_mint(&format!(
+        "
+        local edition = spore_data.params[\"edition\"]
+        local cluster_name = cluster_data.name
+        if spore_ext_mode ~= 0 and #spore_ext_args + spore_output_index + spore_data.content_size == 0 then
+          ckb.exit_script(1)
+        end
+        {MUTANT_ENTRY_POINTS_CODE}"
+    ));
+}
+
+#[should_panic]
+#[test]
+fn test_mutant_mint_failed_with_top_level_check_in_validation() {
+    make_simple_mutant_mint(&format!(
+        "
+        if spore_ext_args ~= \"edition-5\" then
+          ckb.exit_script(1)
+        end
+        {MUTANT_ENTRY_POINTS_CODE}"
+    ));
+}
+
+#[test]
+fn test_structured_mutant_mint() {
+    let abi = r#"{"args":"edition-<n>"}"#;
+    let mutant_data = build_serialized_mutant_data(
+        "Edition".as_bytes(),
+        Some(abi.as_bytes()),
+        &[(1, "sold out".as_bytes())],
+        MUTANT_ENTRY_POINTS_CODE,
+    );
+    make_mutant_mint_with_data(mutant_data.as_slice(), &[]);
+}
+
+#[should_panic]
+#[test]
+fn test_structured_mutant_mint_failed_with_duplicate_errors() {
+    let errors: [(u8, &[u8]); 2] = [(1, "sold out".as_bytes()), (1, "too late".as_bytes())];
+    let mutant_data = build_serialized_mutant_data(
+        "Edition".as_bytes(),
+        None,
+        &errors,
+        MUTANT_ENTRY_POINTS_CODE,
+    );
+    make_mutant_mint_with_data(mutant_data.as_slice(), &[]);
+}
+
+#[should_panic]
+#[test]
+fn test_structured_mutant_mint_failed_with_invalid_error_message() {
+    let mutant_data = build_serialized_mutant_data(
+        "Edition".as_bytes(),
+        None,
+        &[(1, &[0xff, 0xfe])],
+        MUTANT_ENTRY_POINTS_CODE,
+    );
+    make_mutant_mint_with_data(mutant_data.as_slice(), &[]);
+}
+
+#[should_panic]
+#[test]
+fn test_structured_mutant_mint_failed_with_invalid_metadata() {
+    let mutant_data =
+        build_serialized_mutant_data(&[0xff, 0xfe], None, &[], MUTANT_ENTRY_POINTS_CODE);
+    make_mutant_mint_with_data(mutant_data.as_slice(), &[]);
+}
+
+#[should_panic]
+#[test]
+fn test_structured_mutant_mint_failed_without_entry_points() {
+    let mutant_data =
+        build_serialized_mutant_data("Edition".as_bytes(), None, &[], "print('hello')");
+    make_mutant_mint_with_data(mutant_data.as_slice(), &[]);
+}
+
+// payment and mode are both zero, then the memory tier byte follows
+fn build_memory_tier_args_suffix(tier: u8) -> Vec<u8> {
+    vec![0u64.to_le_bytes().to_vec(), vec![0u8, tier]].concat()
+}
+
+#[test]
+fn test_mutant_mint_with_memory_tier() {
+    make_mutant_mint_with_args_suffix(MUTANT_ENTRY_POINTS_CODE, &build_memory_tier_args_suffix(0));
+}
+
+#[should_panic]
+#[test]
+fn test_mutant_mint_failed_with_invalid_memory_tier() {
+    make_mutant_mint_with_args_suffix(MUTANT_ENTRY_POINTS_CODE, &build_memory_tier_args_suffix(4));
+}
+
+#[should_panic]
+#[test]
+fn test_mutant_mint_failed_with_insufficient_memory_tier() {
+    let lua_code =
+        format!("local t = {{}}\nfor i = 1, 100000 do t[i] = i end\n{MUTANT_ENTRY_POINTS_CODE}");
+    make_mutant_mint_with_args_suffix(&lua_code, &build_memory_tier_args_suffix(0));
+}
+
 #[test]
 fn test_simple_mutant_spore_mint_without_cluster() {
     let mut context = Context::default();
@@ -51,7 +271,7 @@ fn test_simple_mutant_spore_mint_without_cluster() {
     let (tx, mutant_id) = build_single_mutant_celldep_tx(&mut context, lua_code, 1);
 
     println!("mutant_id: {mutant_id:?}");
-    let content_type = format!("plain/test;mutant[]={}", hex::encode(mutant_id));
+    let content_type = build_mutant_content_type(&[mutant_id]);
     let (output_data, normal_input, spore_output, spore_celldep) = build_spore_output_materials(
         &mut context,
         "mutant spore".as_bytes().to_vec(),
@@ -73,3 +293,1004 @@ fn test_simple_mutant_spore_mint_without_cluster() {
         .verify_tx(&tx, MAX_CYCLES)
         .expect("test mint mutant spore cell (no cluster)");
 }
+
+fn make_mutant_spore_mint_with_args(lua_code: &str, mutant_args: &str) {
+    let mut context = Context::default();
+
+    let (tx, mutant_id) = build_single_mutant_celldep_tx(&mut context, lua_code, 1);
+
+    let content_type = format!(
+        "plain/test;mutant[]={};mutant_args[]={mutant_args}",
+        hex::encode(mutant_id)
+    );
+    let (output_data, normal_input, spore_output, spore_celldep) = build_spore_output_materials(
+        &mut context,
+        "mutant spore".as_bytes().to_vec(),
+        &content_type,
+        0,
+        None,
+    );
+
+    let tx = tx
+        .as_advanced_builder()
+        .input(normal_input)
+        .output(spore_output)
+        .output_data(output_data.as_bytes().pack())
+        .cell_dep(spore_celldep)
+        .build();
+    let tx = context.complete_tx(tx);
+
+    context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("test mint mutant spore with args");
+}
+
+#[test]
+fn test_mutant_spore_mint_with_args() {
+    let lua_code = "if spore_ext_args ~= 'edition-5' then ckb.exit_script(1) end";
+    make_mutant_spore_mint_with_args(lua_code, "edition-5");
+}
+
+#[should_panic]
+#[test]
+fn test_mutant_spore_mint_failed_with_wrong_args() {
+    let lua_code = "if spore_ext_args ~= 'edition-5' then ckb.exit_script(1) end";
+    make_mutant_spore_mint_with_args(lua_code, "edition-6");
+}
+
+#[test]
+fn test_mutant_spore_mint_with_spore_error_helper() {
+    let lua_code = "if spore_ext_args ~= 'edition-5' then spore_error(3) end";
+    make_mutant_spore_mint_with_args(lua_code, "edition-5");
+}
+
+#[should_panic]
+#[test]
+fn test_mutant_spore_mint_failed_with_spore_error() {
+    let lua_code = "if spore_ext_args ~= 'edition-5' then spore_error(3) end";
+    make_mutant_spore_mint_with_args(lua_code, "edition-6");
+}
+
+#[should_panic]
+#[test]
+fn test_mutant_spore_mint_failed_with_mismatched_args_count() {
+    make_mutant_spore_mint_with_args("print('hello world')", "edition-5,rare");
+}
+
+#[test]
+fn test_mutant_spore_mint_with_decoded_spore_data() {
+    let lua_code = "
+        if spore_data.main_type ~= 'plain' or spore_data.sub_type ~= 'test' then ckb.exit_script(1) end
+        if spore_data.params['mutant_args[]'] ~= 'edition-5' then ckb.exit_script(2) end
+        if spore_data.content ~= 'mutant spore' or spore_data.content_size ~= 12 then ckb.exit_script(3) end
+        if spore_data.cluster_id ~= nil or cluster_data ~= nil then ckb.exit_script(4) end
+    ";
+    make_mutant_spore_mint_with_args(lua_code, "edition-5");
+}
+
+#[should_panic]
+#[test]
+fn test_mutant_spore_mint_failed_with_wrong_decoded_spore_data() {
+    let lua_code = "if spore_data.content_size ~= 0 then ckb.exit_script(1) end";
+    make_mutant_spore_mint_with_args(lua_code, "edition-5");
+}
+
+fn make_cluster_transfer_with_lifecycle_mutant(lua_code: &str, mutant_in_deps: bool) {
+    let mut context = Context::default();
+
+    let (tx, mutant_id) = build_single_mutant_celldep_tx(&mut context, lua_code, 0);
+    let tx = if mutant_in_deps {
+        tx
+    } else {
+        // keep lua lib and extension script only
+        let cell_deps = tx.cell_deps().into_iter().skip(1).collect::<Vec<_>>();
+        tx.as_advanced_builder().set_cell_deps(cell_deps).build()
+    };
+
+    let normal_cell = build_normal_input(&mut context);
+    let (cluster_out_point, cluster_script_dep) =
+        build_spore_contract_materials(&mut context, "cluster");
+    let cluster_data = build_serialized_cluster_data_with_lifecycle_mutant(
+        "Spore Cluster",
+        "Test Cluster Transfer",
+        mutant_id,
+    );
+    let cluster_id = build_type_id(&normal_cell, 0);
+    let cluster_type =
+        build_spore_type_script(&mut context, &cluster_out_point, cluster_id.to_vec().into());
+    let old_cluster_cell = build_cluster_input(
+        &mut context,
+        ClusterDataV2::new_unchecked(cluster_data.as_bytes()),
+        cluster_type.clone(),
+    );
+    let new_cluster_cell = build_normal_output_cell_with_type(&mut context, cluster_type.clone());
+
+    let tx = tx
+        .as_advanced_builder()
+        .input(old_cluster_cell)
+        .output(new_cluster_cell)
+        .output_data(cluster_data.as_slice().pack())
+        .cell_dep(cluster_script_dep)
+        .build();
+
+    let action = build_transfer_cluster_action(&mut context, cluster_id);
+    let tx = complete_co_build_message_with_actions(tx, &[(cluster_type, action)]);
+    let tx = context.complete_tx(tx);
+
+    context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("test cluster transfer with lifecycle mutant");
+}
+
+#[test]
+fn test_cluster_transfer_with_lifecycle_mutant() {
+    let lua_code = "
+        if spore_ext_mode ~= 4 then ckb.exit_script(1) end
+        if cluster_data.name ~= 'Spore Cluster' then ckb.exit_script(2) end
+    ";
+    make_cluster_transfer_with_lifecycle_mutant(lua_code, true);
+}
+
+#[should_panic]
+#[test]
+fn test_cluster_transfer_failed_with_lifecycle_mutant_rejected() {
+    make_cluster_transfer_with_lifecycle_mutant("ckb.exit_script(1)", true);
+}
+
+#[should_panic]
+#[test]
+fn test_cluster_transfer_failed_without_lifecycle_mutant_in_deps() {
+    make_cluster_transfer_with_lifecycle_mutant("print('hello world')", false);
+}
+
+fn make_cluster_proxy_mint_with_lifecycle_mutant(lua_code: &str) {
+    let mut context = Context::default();
+
+    let (tx, mutant_id) = build_single_mutant_celldep_tx(&mut context, lua_code, 0);
+    let input_cell = build_normal_input(&mut context);
+
+    // cluster
+    let cluster = build_serialized_cluster_data_with_lifecycle_mutant(
+        "Spore Cluster",
+        "Test Cluster",
+        mutant_id,
+    );
+    let (cluster_out_point, cluster_script_dep) =
+        build_spore_contract_materials(&mut context, "cluster");
+    let cluster_id = build_type_id(&input_cell, 1);
+    let cluster_type =
+        build_spore_type_script(&mut context, &cluster_out_point, cluster_id.to_vec().into());
+    let cluster_dep = build_normal_cell_dep(&mut context, cluster.as_slice(), cluster_type);
+
+    // proxy
+    let (proxy_out_point, proxy_script_dep) =
+        build_spore_contract_materials(&mut context, "cluster_proxy");
+    let proxy_id = build_type_id(&input_cell, 0);
+    let proxy_type =
+        build_spore_type_script_with_payment(&mut context, &proxy_out_point, &proxy_id, 1);
+    let proxy_out_cell = build_normal_output_cell_with_type(&mut context, proxy_type.clone());
+
+    let tx = tx
+        .as_advanced_builder()
+        .input(input_cell)
+        .output(proxy_out_cell)
+        .output_data(cluster_id.to_vec().pack())
+        .cell_deps(vec![cluster_script_dep, proxy_script_dep, cluster_dep])
+        .build();
+
+    let action = build_mint_proxy_action(&mut context, cluster_id, proxy_id);
+    let tx = complete_co_build_message_with_actions(tx, &[(proxy_type, action)]);
+    let tx = context.complete_tx(tx);
+
+    context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("test cluster proxy mint with lifecycle mutant");
+}
+
+#[test]
+fn test_cluster_proxy_mint_with_lifecycle_mutant() {
+    let lua_code = "
+        function on_proxy_create()
+            if spore_ext_mode ~= 5 then ckb.exit_script(1) end
+            if cluster_data.name ~= 'Spore Cluster' then ckb.exit_script(2) end
+            if cluster_dep_index ~= 5 or proxy_output_index ~= 0 then ckb.exit_script(3) end
+        end
+    ";
+    make_cluster_proxy_mint_with_lifecycle_mutant(lua_code);
+}
+
+#[should_panic]
+#[test]
+fn test_cluster_proxy_mint_failed_with_lifecycle_mutant_rejected() {
+    make_cluster_proxy_mint_with_lifecycle_mutant(
+        "function on_proxy_create() ckb.exit_script(1) end",
+    );
+}
+
+fn make_cluster_agent_mint_with_lifecycle_mutant(lua_code: &str, add_cluster_dep: bool) {
+    let mut context = Context::default();
+
+    let (tx, mutant_id) = build_single_mutant_celldep_tx(&mut context, lua_code, 0);
+    let input_cell = build_normal_input(&mut context);
+
+    // cluster
+    let cluster = build_serialized_cluster_data_with_lifecycle_mutant(
+        "Spore Cluster",
+        "Test Cluster",
+        mutant_id,
+    );
+    let (cluster_out_point, cluster_script_dep) =
+        build_spore_contract_materials(&mut context, "cluster");
+    let cluster_id = build_type_id(&input_cell, 0);
+    let cluster_type =
+        build_spore_type_script(&mut context, &cluster_out_point, cluster_id.to_vec().into());
+    let cluster_dep = build_normal_cell_dep(&mut context, cluster.as_slice(), cluster_type);
+
+    // proxy
+    let (proxy_out_point, proxy_script_dep) =
+        build_spore_contract_materials(&mut context, "cluster_proxy");
+    let proxy_id = build_type_id(&input_cell, 1);
+    let proxy_type =
+        build_spore_type_script_with_payment(&mut context, &proxy_out_point, &proxy_id, 1);
+    let proxy_dep = build_normal_cell_dep(&mut context, &cluster_id, proxy_type.clone());
+    let proxy_type_hash = proxy_type.unwrap_or_default().calc_script_hash();
+
+    // agent
+    let (agent_out_point, agent_script_dep) =
+        build_spore_contract_materials(&mut context, "cluster_agent");
+    let agent_type =
+        build_spore_type_script(&mut context, &agent_out_point, cluster_id.to_vec().into());
+    let agent_out_cell = build_normal_output_cell_with_type(&mut context, agent_type.clone())
+        .as_builder()
+        .capacity((2 * CAPACITY_UNIT).pack())
+        .build();
+
+    let mut tx = tx
+        .as_advanced_builder()
+        .input(input_cell)
+        .output(agent_out_cell)
+        .output_data(proxy_type_hash.as_slice().pack())
+        .cell_deps(vec![
+            cluster_script_dep,
+            proxy_script_dep,
+            agent_script_dep,
+            proxy_dep,
+        ])
+        .build();
+    if add_cluster_dep {
+        tx = tx.as_advanced_builder().cell_dep(cluster_dep).build();
+    }
+
+    let action = build_mint_agent_action(&mut context, cluster_id, proxy_id);
+    let tx = complete_co_build_message_with_actions(tx, &[(agent_type, action)]);
+    let tx = context.complete_tx(tx);
+
+    context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("test cluster agent mint with lifecycle mutant");
+}
+
+#[test]
+fn test_cluster_agent_mint_with_lifecycle_mutant() {
+    let lua_code = "
+        function on_agent_create()
+            if spore_ext_mode ~= 6 then ckb.exit_script(1) end
+            if cluster_data.name ~= 'Spore Cluster' then ckb.exit_script(2) end
+            if cluster_dep_index ~= 7 or proxy_dep_index ~= 6 or agent_output_index ~= 0 then
+                ckb.exit_script(3)
+            end
+        end
+    ";
+    make_cluster_agent_mint_with_lifecycle_mutant(lua_code, true);
+}
+
+#[should_panic]
+#[test]
+fn test_cluster_agent_mint_failed_with_lifecycle_mutant_rejected() {
+    make_cluster_agent_mint_with_lifecycle_mutant(
+        "function on_agent_create() ckb.exit_script(1) end",
+        true,
+    );
+}
+
+#[test]
+fn test_cluster_agent_mint_without_cluster_dep() {
+    // the lifecycle mutant can't run without the cluster, which is optional for agent creation
+    make_cluster_agent_mint_with_lifecycle_mutant(
+        "function on_agent_create() ckb.exit_script(1) end",
+        false,
+    );
+}
+
+fn make_multiple_mutants_spore_mint(first_lua_code: &str, second_lua_code: &str) {
+    let mut context = Context::default();
+
+    let (tx, first_mutant_id) = build_single_mutant_celldep_tx(&mut context, first_lua_code, 1);
+    let (second_tx, second_mutant_id) =
+        build_single_mutant_celldep_tx(&mut context, second_lua_code, 1);
+    let second_mutant_celldep = second_tx.cell_deps().get(0).unwrap();
+
+    let content_type = build_mutant_content_type(&[first_mutant_id, second_mutant_id]);
+    let (output_data, normal_input, spore_output, spore_celldep) = build_spore_output_materials(
+        &mut context,
+        "mutant spore".as_bytes().to_vec(),
+        &content_type,
+        0,
+        None,
+    );
+
+    let tx = tx
+        .as_advanced_builder()
+        .input(normal_input)
+        .output(spore_output)
+        .output_data(output_data.as_bytes().pack())
+        .cell_dep(second_mutant_celldep)
+        .cell_dep(spore_celldep)
+        .build();
+    let tx = context.complete_tx(tx);
+
+    context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("test mint spore with multiple mutants");
+}
+
+#[test]
+fn test_multiple_mutants_spore_mint() {
+    make_multiple_mutants_spore_mint("print('first mutant')", "print('second mutant')");
+}
+
+#[should_panic]
+#[test]
+fn test_multiple_mutants_spore_mint_failed_with_second_mutant_rejected() {
+    make_multiple_mutants_spore_mint("print('first mutant')", "ckb.exit_script(1)");
+}
+
+#[test]
+fn test_multiple_mutants_spore_mint_in_sandbox() {
+    make_multiple_mutants_spore_mint(
+        "assert(load == nil and debug == nil and getmetatable == nil and rawget == nil and package == nil)",
+        "assert(ckb.spawn_cell == nil and ckb.exit_script ~= nil and ckb.SOURCE_INPUT ~= nil)",
+    );
+}
+
+// the cluster requires the second mutant, and the first one runs ahead of it in the same Lua instance
+fn make_cluster_required_mutant_spore_mint(first_lua_code: &str, second_lua_code: &str) {
+    let mut context = Context::default();
+
+    let (tx, first_mutant_id) = build_single_mutant_celldep_tx(&mut context, first_lua_code, 1);
+    let (second_tx, second_mutant_id) =
+        build_single_mutant_celldep_tx(&mut context, second_lua_code, 1);
+    let second_mutant_celldep = second_tx.cell_deps().get(0).unwrap();
+
+    // spore is minted by the cluster lock proxy
+    let (cluster_out_point, _) = build_spore_contract_materials(&mut context, "cluster");
+    let cluster_data = NativeClusterDataV2 {
+        mutant_id: Some(second_mutant_id.to_vec()),
+        ..NativeClusterDataV2::new("Spore Cluster", "Test Cluster")
+    };
+    let (cluster_id, _, _, _, cluster_dep) = build_cluster_materials(
+        &mut context,
+        &cluster_out_point,
+        cluster_data.into(),
+        0,
+        &[],
+    );
+
+    let content_type = build_mutant_content_type(&[first_mutant_id, second_mutant_id]);
+    let spore_tx = build_single_spore_mint_tx(
+        &mut context,
+        "mutant spore".as_bytes().to_vec(),
+        &content_type,
+        None,
+        Some(cluster_id),
+    );
+    let tx = spore_tx
+        .as_advanced_builder()
+        .cell_deps(tx.cell_deps())
+        .cell_dep(second_mutant_celldep)
+        .cell_dep(cluster_dep)
+        .build();
+    let tx = context.complete_tx(tx);
+
+    context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("test mint spore with cluster required mutant");
+}
+
+#[test]
+fn test_cluster_required_mutant_spore_mint() {
+    make_cluster_required_mutant_spore_mint("print('first mutant')", "print('required mutant')");
+}
+
+#[should_panic]
+#[test]
+fn test_cluster_required_mutant_spore_mint_failed_with_exit_script_patched() {
+    make_cluster_required_mutant_spore_mint(
+        "ckb.exit_script = function() end",
+        "ckb.exit_script(1)",
+    );
+}
+
+#[should_panic]
+#[test]
+fn test_cluster_required_mutant_spore_mint_failed_with_error_patched() {
+    make_cluster_required_mutant_spore_mint(
+        "error = function() end\nassert = function() return true end",
+        "assert(false)",
+    );
+}
+
+fn make_mutant_spore_mint_with_payment_recipient(pay_to_recipient: bool) {
+    let mut context = Context::default();
+
+    let payment = 100;
+    let recipient_output = build_normal_output_with_lock_args(&mut context, payment, &[9]);
+    let recipient_lock_hash = recipient_output.lock().calc_script_hash();
+    let args_suffix = vec![
+        payment.to_le_bytes().to_vec(),
+        recipient_lock_hash.as_slice().to_vec(),
+    ]
+    .concat();
+    let (tx, mutant_id) = build_single_mutant_celldep_tx_with_args_suffix(
+        &mut context,
+        MUTANT_ENTRY_POINTS_CODE,
+        1,
+        &args_suffix,
+    );
+
+    let content_type = build_mutant_content_type(&[mutant_id]);
+    let (output_data, normal_input, spore_output, spore_celldep) = build_spore_output_materials(
+        &mut context,
+        "mutant spore".as_bytes().to_vec(),
+        &content_type,
+        0,
+        None,
+    );
+
+    // mutant cell is locked by the lock with mutant id as args
+    let payment_output = if pay_to_recipient {
+        recipient_output
+    } else {
+        build_normal_output_with_lock_args(&mut context, payment, &mutant_id)
+    };
+    let tx = tx
+        .as_advanced_builder()
+        .input(normal_input)
+        .output(spore_output)
+        .output_data(output_data.as_bytes().pack())
+        .output(payment_output)
+        .output_data(Default::default())
+        .cell_dep(spore_celldep)
+        .build();
+    let tx = context.complete_tx(tx);
+
+    context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("test mint mutant spore with payment recipient");
+}
+
+#[test]
+fn test_mutant_spore_mint_with_payment_recipient() {
+    make_mutant_spore_mint_with_payment_recipient(true);
+}
+
+#[should_panic]
+#[test]
+fn test_mutant_spore_mint_failed_with_payment_to_mutant_lock() {
+    make_mutant_spore_mint_with_payment_recipient(false);
+}
+
+fn make_mutant_spore_transfer_with_payment(paid: bool) {
+    let mut context = Context::default();
+
+    // mint is free, transfer pays to the recipient and burn is free
+    let transfer_payment = 100u64;
+    let recipient_output = build_normal_output_with_lock_args(&mut context, transfer_payment, &[9]);
+    let recipient_lock_hash = recipient_output.lock().calc_script_hash();
+    let args_suffix = vec![
+        0u64.to_le_bytes().to_vec(),
+        recipient_lock_hash.as_slice().to_vec(),
+        transfer_payment.to_le_bytes().to_vec(),
+        0u64.to_le_bytes().to_vec(),
+    ]
+    .concat();
+    let (tx, mutant_id) = build_single_mutant_celldep_tx_with_args_suffix(
+        &mut context,
+        MUTANT_ENTRY_POINTS_CODE,
+        1,
+        &args_suffix,
+    );
+
+    let (spore_out_point, spore_script_dep) = build_spore_contract_materials(&mut context, "spore");
+    let content_type = build_mutant_content_type(&[mutant_id]);
+    let serialized =
+        build_serialized_spore_data("mutant spore".as_bytes().to_vec(), &content_type, None);
+    let normal_input = build_normal_input(&mut context);
+    let spore_id = build_type_id(&normal_input, 0);
+    let spore_type =
+        build_spore_type_script(&mut context, &spore_out_point, spore_id.to_vec().into());
+    let spore_input = build_spore_input(&mut context, spore_type.clone(), serialized.clone());
+    let spore_output = build_normal_output_cell_with_type(&mut context, spore_type.clone());
+
+    let mut tx = tx
+        .as_advanced_builder()
+        .input(spore_input)
+        .output(spore_output)
+        .output_data(serialized.as_slice().pack())
+        .cell_dep(spore_script_dep)
+        .build();
+    if paid {
+        tx = tx
+            .as_advanced_builder()
+            .output(recipient_output)
+            .output_data(Default::default())
+            .build();
+    }
+
+    let action = build_transfer_spore_action(&mut context, spore_id);
+    let tx = complete_co_build_message_with_actions(tx, &[(spore_type, action)]);
+    let tx = context.complete_tx(tx);
+
+    context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("test transfer mutant spore with payment");
+}
+
+#[test]
+fn test_mutant_spore_transfer_with_payment() {
+    make_mutant_spore_transfer_with_payment(true);
+}
+
+#[should_panic]
+#[test]
+fn test_mutant_spore_transfer_failed_without_payment() {
+    make_mutant_spore_transfer_with_payment(false);
+}
+
+fn make_mutant_spore_burn_with_payment(paid: bool, zero_recipient: bool) {
+    let mut context = Context::default();
+
+    // mint and transfer are free, burn pays to the recipient
+    let burn_payment = 100u64;
+    let recipient_output = build_normal_output_with_lock_args(&mut context, burn_payment, &[9]);
+    let recipient_lock_hash = if zero_recipient {
+        [0u8; 32]
+    } else {
+        recipient_output.lock().calc_script_hash().unpack().0
+    };
+    let args_suffix = vec![
+        0u64.to_le_bytes().to_vec(),
+        recipient_lock_hash.to_vec(),
+        0u64.to_le_bytes().to_vec(),
+        burn_payment.to_le_bytes().to_vec(),
+    ]
+    .concat();
+    let (tx, mutant_id) = build_single_mutant_celldep_tx_with_args_suffix(
+        &mut context,
+        MUTANT_ENTRY_POINTS_CODE,
+        1,
+        &args_suffix,
+    );
+
+    let (spore_out_point, spore_script_dep) = build_spore_contract_materials(&mut context, "spore");
+    let content_type = build_mutant_content_type(&[mutant_id]);
+    let serialized =
+        build_serialized_spore_data("mutant spore".as_bytes().to_vec(), &content_type, None);
+    let normal_input = build_normal_input(&mut context);
+    let spore_id = build_type_id(&normal_input, 0);
+    let spore_type =
+        build_spore_type_script(&mut context, &spore_out_point, spore_id.to_vec().into());
+    let spore_input = build_spore_input(&mut context, spore_type.clone(), serialized);
+
+    let mut tx = tx
+        .as_advanced_builder()
+        .input(spore_input)
+        .output(build_normal_output(&mut context))
+        .output_data(Default::default())
+        .cell_dep(spore_script_dep)
+        .build();
+    if paid {
+        tx = tx
+            .as_advanced_builder()
+            .output(recipient_output)
+            .output_data(Default::default())
+            .build();
+    }
+
+    let action = build_burn_spore_action(&mut context, spore_id);
+    let tx = complete_co_build_message_with_actions(tx, &[(spore_type, action)]);
+    let tx = context.complete_tx(tx);
+
+    context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("test burn mutant spore with payment");
+}
+
+#[test]
+fn test_mutant_spore_burn_with_payment() {
+    make_mutant_spore_burn_with_payment(true, false);
+}
+
+#[should_panic]
+#[test]
+fn test_mutant_spore_burn_failed_without_payment() {
+    make_mutant_spore_burn_with_payment(false, false);
+}
+
+#[should_panic]
+#[test]
+fn test_mutant_spore_burn_failed_with_zero_recipient() {
+    make_mutant_spore_burn_with_payment(true, true);
+}
+
+#[should_panic]
+#[test]
+fn test_simple_mutant_mint_failed_with_zero_payment_recipient() {
+    let args_suffix = vec![0u64.to_le_bytes().to_vec(), vec![0u8; 32]].concat();
+    make_mutant_mint_with_args_suffix(MUTANT_ENTRY_POINTS_CODE, &args_suffix);
+}
+
+fn make_mutant_spore_mint_at_high_index(lua_code: &str, filler_count: usize) {
+    let mut context = Context::default();
+
+    let (tx, mutant_id) = build_single_mutant_celldep_tx(&mut context, lua_code, 1);
+
+    // push mutant cell dep and spore output beyond single digit indices
+    let filler_deps = (0..filler_count)
+        .map(|_| build_normal_cell_dep(&mut context, &[], None))
+        .collect::<Vec<_>>();
+    let cell_deps = filler_deps
+        .into_iter()
+        .chain(tx.cell_deps())
+        .collect::<Vec<_>>();
+    let filler_outputs = (0..filler_count)
+        .map(|_| build_normal_output(&mut context))
+        .collect::<Vec<_>>();
+
+    let content_type = build_mutant_content_type(&[mutant_id]);
+    let (output_data, normal_input, spore_output, spore_celldep) = build_spore_output_materials(
+        &mut context,
+        "mutant spore".as_bytes().to_vec(),
+        &content_type,
+        filler_count,
+        None,
+    );
+
+    let tx = tx
+        .as_advanced_builder()
+        .set_cell_deps(cell_deps)
+        .input(normal_input)
+        .outputs(filler_outputs)
+        .outputs_data(vec![packed::Bytes::default(); filler_count])
+        .output(spore_output)
+        .output_data(output_data.as_bytes().pack())
+        .cell_dep(spore_celldep)
+        .build();
+    let tx = context.complete_tx(tx);
+
+    context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("test mint mutant spore at high index");
+}
+
+#[test]
+fn test_mutant_spore_mint_at_high_index() {
+    let lua_code = "if spore_output_index ~= 12 then ckb.exit_script(1) end";
+    make_mutant_spore_mint_at_high_index(lua_code, 12);
+}
+
+#[should_panic]
+#[test]
+fn test_mutant_spore_mint_failed_at_wrong_high_index() {
+    let lua_code = "if spore_output_index ~= 12 then ckb.exit_script(1) end";
+    make_mutant_spore_mint_at_high_index(lua_code, 11);
+}
+
+fn make_updatable_mutant_transfer(
+    old_version: u64,
+    new_version: u64,
+    new_lua_code: &str,
+    updatable: bool,
+) {
+    let mut context = Context::default();
+
+    let (_, lua_lib_dep) = build_spore_contract_materials(&mut context, "libckblua.so");
+    let (spore_extension_out_point, spore_extension_script_dep) =
+        build_spore_contract_materials(&mut context, "spore_extension_lua");
+
+    let mutant_id = build_type_id(&build_normal_input(&mut context), 0);
+    let (type_, old_data, new_data) = if updatable {
+        (
+            build_updatable_mutant_type_script(
+                &mut context,
+                &spore_extension_out_point,
+                &mutant_id,
+            ),
+            build_updatable_mutant_data(old_version, "print('version old')"),
+            build_updatable_mutant_data(new_version, new_lua_code),
+        )
+    } else {
+        (
+            build_spore_type_script(
+                &mut context,
+                &spore_extension_out_point,
+                mutant_id.to_vec().into(),
+            ),
+            "print('version old')".as_bytes().to_vec(),
+            new_lua_code.as_bytes().to_vec(),
+        )
+    };
+    let mutant_input = build_agent_proxy_input(&mut context, &old_data, type_.clone());
+    let mutant_output = build_normal_output_cell_with_type(&mut context, type_);
+
+    let tx = TransactionBuilder::default()
+        .input(mutant_input)
+        .output(mutant_output)
+        .output_data(new_data.pack())
+        .cell_deps(vec![lua_lib_dep, spore_extension_script_dep])
+        .build();
+    let tx = context.complete_tx(tx);
+
+    context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("test update mutant");
+}
+
+#[test]
+fn test_updatable_mutant_update() {
+    make_updatable_mutant_transfer(1, 2, MUTANT_ENTRY_POINTS_CODE, true);
+}
+
+#[should_panic]
+#[test]
+fn test_updatable_mutant_update_failed_without_version_increased() {
+    make_updatable_mutant_transfer(1, 1, MUTANT_ENTRY_POINTS_CODE, true);
+}
+
+#[should_panic]
+#[test]
+fn test_updatable_mutant_update_failed_without_entry_points() {
+    make_updatable_mutant_transfer(1, 2, "print('version new')", true);
+}
+
+#[should_panic]
+#[test]
+fn test_normal_mutant_update_failed() {
+    make_updatable_mutant_transfer(0, 0, MUTANT_ENTRY_POINTS_CODE, false);
+}
+
+fn make_spore_mint_with_pinned_mutant_version(mutant_version: u64, pinned_version: Option<u64>) {
+    let mut context = Context::default();
+
+    let (tx, mutant_id) = build_single_updatable_mutant_celldep_tx(
+        &mut context,
+        "print('hello world')",
+        mutant_version,
+        1,
+    );
+
+    let content_type = match pinned_version {
+        Some(version) => Mime::new("plain", "test")
+            .pinned_mutant(mutant_id, version)
+            .to_string(),
+        None => build_mutant_content_type(&[mutant_id]),
+    };
+    let (output_data, normal_input, spore_output, spore_celldep) = build_spore_output_materials(
+        &mut context,
+        "mutant spore".as_bytes().to_vec(),
+        &content_type,
+        0,
+        None,
+    );
+
+    let tx = tx
+        .as_advanced_builder()
+        .input(normal_input)
+        .output(spore_output)
+        .output_data(output_data.as_bytes().pack())
+        .cell_dep(spore_celldep)
+        .build();
+    let tx = context.complete_tx(tx);
+
+    context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("test mint spore with pinned mutant version");
+}
+
+#[test]
+fn test_spore_mint_with_latest_mutant_version() {
+    make_spore_mint_with_pinned_mutant_version(3, None);
+}
+
+#[test]
+fn test_spore_mint_with_pinned_mutant_version() {
+    make_spore_mint_with_pinned_mutant_version(3, Some(3));
+}
+
+#[should_panic]
+#[test]
+fn test_spore_mint_failed_with_mismatched_mutant_version() {
+    make_spore_mint_with_pinned_mutant_version(3, Some(2));
+}
+
+fn make_pinned_spore_operation_after_mutant_updated(burn: bool) {
+    let mut context = Context::default();
+
+    // spore was minted with version 2, and the mutant has been updated to version 3 since then
+    let (tx, mutant_id) =
+        build_single_updatable_mutant_celldep_tx(&mut context, MUTANT_ENTRY_POINTS_CODE, 3, 1);
+
+    let (spore_out_point, spore_script_dep) = build_spore_contract_materials(&mut context, "spore");
+    let content_type = Mime::new("plain", "test")
+        .pinned_mutant(mutant_id, 2)
+        .to_string();
+    let serialized =
+        build_serialized_spore_data("mutant spore".as_bytes().to_vec(), &content_type, None);
+    let normal_input = build_normal_input(&mut context);
+    let spore_id = build_type_id(&normal_input, 0);
+    let spore_type =
+        build_spore_type_script(&mut context, &spore_out_point, spore_id.to_vec().into());
+    let spore_input = build_spore_input(&mut context, spore_type.clone(), serialized.clone());
+
+    let mut tx = tx
+        .as_advanced_builder()
+        .input(spore_input)
+        .cell_dep(spore_script_dep)
+        .build();
+    let action = if burn {
+        tx = tx
+            .as_advanced_builder()
+            .output(build_normal_output(&mut context))
+            .output_data(Default::default())
+            .build();
+        build_burn_spore_action(&mut context, spore_id)
+    } else {
+        let spore_output = build_normal_output_cell_with_type(&mut context, spore_type.clone());
+        tx = tx
+            .as_advanced_builder()
+            .output(spore_output)
+            .output_data(serialized.as_slice().pack())
+            .build();
+        build_transfer_spore_action(&mut context, spore_id)
+    };
+    let tx = complete_co_build_message_with_actions(tx, &[(spore_type, action)]);
+    let tx = context.complete_tx(tx);
+
+    context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("test operate pinned spore after mutant updated");
+}
+
+#[test]
+fn test_pinned_spore_transfer_after_mutant_updated() {
+    make_pinned_spore_operation_after_mutant_updated(false);
+}
+
+#[test]
+fn test_pinned_spore_burn_after_mutant_updated() {
+    make_pinned_spore_operation_after_mutant_updated(true);
+}
+
+const RULES_ALL_OPERATIONS: u8 = 0b111;
+
+fn make_rules_mutant_creation(holding_period: Option<u64>) {
+    let mut context = Context::default();
+
+    let (rules_extension_out_point, rules_extension_script_dep) =
+        build_spore_contract_materials(&mut context, "spore_extension_rules");
+
+    let input_cell = build_normal_input(&mut context);
+    let mutant_id = build_type_id(&input_cell, 0);
+    let type_ = build_spore_type_script(
+        &mut context,
+        &rules_extension_out_point,
+        mutant_id.to_vec().into(),
+    );
+    let rules = build_serialized_mutant_rules(RULES_ALL_OPERATIONS, vec![], holding_period);
+
+    let tx = TransactionBuilder::default()
+        .input(input_cell)
+        .output(build_normal_output_cell_with_type(&mut context, type_))
+        .output_data(rules.as_bytes().pack())
+        .cell_dep(rules_extension_script_dep)
+        .build();
+    let tx = context.complete_tx(tx);
+
+    context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("test create rules mutant");
+}
+
+#[test]
+fn test_rules_mutant_creation() {
+    // relative since of 100 blocks
+    make_rules_mutant_creation(Some(0x8000_0000_0000_0064));
+}
+
+#[should_panic]
+#[test]
+fn test_rules_mutant_creation_failed_with_absolute_holding_period() {
+    make_rules_mutant_creation(Some(0x0000_0000_0000_0064));
+}
+
+// spore is minted with a Lua mutant in front of the rules mutant if `lua_code` is set, and the
+// recipient lock is allowed by rules if `recipient_allowed` is true
+fn make_rules_mutant_spore_mint(
+    allowed_operations: u8,
+    recipient_allowed: bool,
+    lua_code: Option<&str>,
+) {
+    let mut context = Context::default();
+
+    let (output_data, normal_input, spore_output, spore_celldep) = build_spore_output_materials(
+        &mut context,
+        "rules spore".as_bytes().to_vec(),
+        "plain/test",
+        0,
+        None,
+    );
+    let recipient_lock_code_hash = if recipient_allowed {
+        spore_output.lock().code_hash().unpack().0
+    } else {
+        [0xffu8; 32]
+    };
+    let rules =
+        build_serialized_mutant_rules(allowed_operations, vec![recipient_lock_code_hash], None);
+    let (tx, rules_mutant_id) = build_single_rules_mutant_celldep_tx(&mut context, &rules, 1);
+
+    let mut mutant_ids = vec![rules_mutant_id];
+    let mut cell_deps = tx.cell_deps().into_iter().collect::<Vec<_>>();
+    if let Some(lua_code) = lua_code {
+        let (lua_tx, lua_mutant_id) = build_single_mutant_celldep_tx(&mut context, lua_code, 1);
+        mutant_ids.insert(0, lua_mutant_id);
+        cell_deps.extend(lua_tx.cell_deps());
+    }
+
+    let content_type = build_mutant_content_type(&mutant_ids);
+    let output_data = build_serialized_spore_data(
+        output_data.content().raw_data().to_vec(),
+        &content_type,
+        None,
+    );
+    let tx = tx
+        .as_advanced_builder()
+        .set_cell_deps(cell_deps)
+        .input(normal_input)
+        .output(spore_output)
+        .output_data(output_data.as_bytes().pack())
+        .cell_dep(spore_celldep)
+        .build();
+    let tx = context.complete_tx(tx);
+
+    context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("test mint spore with rules mutant");
+}
+
+#[test]
+fn test_rules_mutant_spore_mint() {
+    make_rules_mutant_spore_mint(RULES_ALL_OPERATIONS, true, None);
+}
+
+#[should_panic]
+#[test]
+fn test_rules_mutant_spore_mint_failed_with_mint_forbidden() {
+    make_rules_mutant_spore_mint(RULES_ALL_OPERATIONS & !0b001, true, None);
+}
+
+#[should_panic]
+#[test]
+fn test_rules_mutant_spore_mint_failed_with_recipient_not_allowed() {
+    make_rules_mutant_spore_mint(RULES_ALL_OPERATIONS, false, None);
+}
+
+#[test]
+fn test_rules_and_lua_mutants_spore_mint() {
+    make_rules_mutant_spore_mint(RULES_ALL_OPERATIONS, true, Some("print('lua mutant')"));
+}
+
+#[should_panic]
+#[test]
+fn test_rules_and_lua_mutants_spore_mint_failed_with_rules_rejected() {
+    make_rules_mutant_spore_mint(RULES_ALL_OPERATIONS, false, Some("print('lua mutant')"));
+}
@@ -8,6 +8,9 @@ use crate::MAX_CYCLES;
 
 mod simple_spore_mint {
     use super::*;
+    use spore_types::generated::hashref::ContentHashRef;
+    use spore_types::hashref::{HashAlgorithm, NativeContentHashRef, HASHREF_CONTENT_TYPE};
+    use spore_utils::{encode_content, ContentEncoding};
     use std::fs::File;
     use std::io::{BufReader, Read};
 
@@ -35,6 +38,19 @@ mod simple_spore_mint {
         make_simple_spore_mint(buffer, "image/jpeg").unwrap();
     }
 
+    #[test]
+    fn test_simple_spore_mint_from_strict_jpeg_image() {
+        let jpeg = std::fs::read("resource/test.jpg").unwrap();
+        make_simple_spore_mint(jpeg, "image/jpeg;strict=true").unwrap();
+    }
+
+    #[should_panic]
+    #[test]
+    fn test_simple_spore_mint_failed_with_mismatched_strict_image() {
+        let jpeg = std::fs::read("resource/test.jpg").unwrap();
+        make_simple_spore_mint(jpeg, "image/png;strict=true").unwrap();
+    }
+
     #[should_panic]
     #[test]
     fn test_simple_spore_mint_failed_with_empty_content() {
@@ -59,6 +75,104 @@ mod simple_spore_mint {
 
         assert!(all_failed == false, "all failed");
     }
+
+    #[test]
+    fn test_simple_spore_mint_with_validated_content() {
+        make_simple_spore_mint(
+            "你好 Spore!".as_bytes().to_vec(),
+            "text/plain;charset=utf-8",
+        )
+        .unwrap();
+        make_simple_spore_mint(
+            r#"{"name": "Spore", "tags": ["nft", 1, true, null]}"#
+                .as_bytes()
+                .to_vec(),
+            "application/json",
+        )
+        .unwrap();
+        // content is unchecked without charset
+        make_simple_spore_mint(vec![0xff, 0xfe], "text/plain").unwrap();
+    }
+
+    #[should_panic]
+    #[test]
+    fn test_simple_spore_mint_failed_with_invalid_utf8_content() {
+        make_simple_spore_mint(vec![0xff, 0xfe], "text/markdown;charset=UTF-8").unwrap();
+    }
+
+    #[should_panic]
+    #[test]
+    fn test_simple_spore_mint_failed_with_malformed_json_content() {
+        make_simple_spore_mint(
+            r#"{"name": "Spore", "tags": ["nft",]}"#.as_bytes().to_vec(),
+            "application/ld+json",
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_simple_spore_mint_with_encoded_content() {
+        let svg = r#"<svg xmlns="http://www.w3.org/2000/svg"><rect width="10"/></svg>"#.repeat(16);
+        let gzip = encode_content(ContentEncoding::Gzip, svg.as_bytes());
+        make_simple_spore_mint(gzip, "image/svg+xml;encoding=gzip;strict=true").unwrap();
+        let zstd = encode_content(ContentEncoding::Zstd, br#"{"name": "Spore"}"#);
+        make_simple_spore_mint(zstd, "application/json;encoding=zstd;strict=true").unwrap();
+        // brotli content can only be minted without decoding check
+        let brotli = encode_content(ContentEncoding::Brotli, svg.as_bytes());
+        make_simple_spore_mint(brotli, "image/svg+xml;encoding=brotli").unwrap();
+    }
+
+    #[should_panic]
+    #[test]
+    fn test_simple_spore_mint_failed_with_brotli_json_content() {
+        // JSON content asks for validation, which requires decoding brotli on-chain
+        let brotli = encode_content(ContentEncoding::Brotli, br#"{"name": "Spore"}"#);
+        make_simple_spore_mint(brotli, "application/json;encoding=brotli").unwrap();
+    }
+
+    #[should_panic]
+    #[test]
+    fn test_simple_spore_mint_failed_with_corrupted_encoded_content() {
+        let mut gzip = encode_content(ContentEncoding::Gzip, "THIS IS A TEST NFT".as_bytes());
+        let len = gzip.len();
+        gzip[len - 8] ^= 0xff;
+        make_simple_spore_mint(gzip, "text/plain;encoding=gzip;strict=true").unwrap();
+    }
+
+    #[test]
+    fn test_simple_spore_mint_with_hashref_content() {
+        let jpeg = std::fs::read("resource/test.jpg").unwrap();
+        let hashref = NativeContentHashRef::new(
+            HashAlgorithm::Blake2b256,
+            &jpeg,
+            vec!["ipfs://bafybeigdyrzt5sfp7udm7hu76uh7y26nf3efuylqabf3oclgtqy55fbzdi".to_owned()],
+        );
+        let content: ContentHashRef = hashref.into();
+        make_simple_spore_mint(content.as_slice().to_vec(), HASHREF_CONTENT_TYPE).unwrap();
+    }
+
+    #[should_panic]
+    #[test]
+    fn test_simple_spore_mint_failed_with_invalid_hashref_content() {
+        let hashref = NativeContentHashRef {
+            algorithm: HashAlgorithm::Sha256,
+            hash: [0xab; 32],
+            size: 1024,
+            uris: vec![],
+        };
+        let content: ContentHashRef = hashref.into();
+        make_simple_spore_mint(content.as_slice().to_vec(), HASHREF_CONTENT_TYPE).unwrap();
+    }
+
+    #[should_panic]
+    #[test]
+    fn test_simple_spore_mint_failed_with_unsupported_encoding() {
+        make_simple_spore_mint(
+            "THIS IS A TEST NFT".as_bytes().to_vec(),
+            "text/plain;encoding=lzma",
+        )
+        .unwrap();
+    }
 }
 
 #[test]
@@ -144,6 +258,7 @@ fn test_multi_spores_mint() {
 
 mod spore_multipart_mint {
     use super::*;
+    use spore_utils::MAX_MULTIPART_PARTS;
 
     fn make_spore_multipart_mint(output_data: &str, content_type: &str) {
         let mut context = Context::default();
@@ -162,11 +277,46 @@ mod spore_multipart_mint {
 
     #[test]
     fn test_spore_multipart_mint() {
+        let output_data = "THIS IS A TEST MULTIPART NFT\n--SporeDefaultBoundary\r\nContent-Type: text/plain; charset=utf-8\r\n\r\nThis is an extra message I want to include\r\n--SporeDefaultBoundary\r\n\r\nThis is another one\r\n--SporeDefaultBoundary--\r\n";
+        let content_type = "multipart/mixed;boundary=SporeDefaultBoundary";
+        make_spore_multipart_mint(output_data, content_type);
+    }
+
+    #[should_panic]
+    #[test]
+    fn test_spore_multipart_mint_failed_without_close_delimiter() {
         let output_data = "THIS IS A TEST MULTIPART NFT\n\n--SporeDefaultBoundary\nThis is an extra message I want to include";
         let content_type = "multipart/mixed;boundary=SporeDefaultBoundary";
         make_spore_multipart_mint(output_data, content_type);
     }
 
+    #[should_panic]
+    #[test]
+    fn test_spore_multipart_mint_failed_with_invalid_part_content_type() {
+        let output_data = "--SporeDefaultBoundary\nContent-Type: text\n\nThis is an extra message I want to include\n--SporeDefaultBoundary--";
+        let content_type = "multipart/mixed;boundary=SporeDefaultBoundary";
+        make_spore_multipart_mint(output_data, content_type);
+    }
+
+    #[should_panic]
+    #[test]
+    fn test_spore_multipart_mint_failed_with_too_many_parts() {
+        let output_data = format!(
+            "{}--SporeDefaultBoundary--",
+            "--SporeDefaultBoundary\n\npart\n".repeat(MAX_MULTIPART_PARTS + 1)
+        );
+        let content_type = "multipart/mixed;boundary=SporeDefaultBoundary";
+        make_spore_multipart_mint(&output_data, content_type);
+    }
+
+    #[should_panic]
+    #[test]
+    fn test_spore_multipart_mint_failed_with_encoding() {
+        let output_data = "--SporeDefaultBoundary\n\npart\n--SporeDefaultBoundary--";
+        let content_type = "multipart/mixed;boundary=SporeDefaultBoundary;encoding=gzip";
+        make_spore_multipart_mint(output_data, content_type);
+    }
+
     #[should_panic]
     #[test]
     fn test_spore_multipart_mint_with_wrong_boundary_name() {
@@ -193,10 +343,72 @@ mod spore_multipart_mint {
     }
 }
 
+mod spore_ref_mint {
+    use super::*;
+
+    fn make_spore_ref_mint(content: &str, content_type: &str, referred_in_deps: bool) {
+        let mut context = Context::default();
+        let referred_id = [0x5a; 32];
+        let content = content.replace("{referred_id}", &hex::encode(referred_id));
+        let tx = build_single_spore_mint_tx(
+            &mut context,
+            content.into_bytes(),
+            content_type,
+            None,
+            None,
+        );
+
+        // build referred spore cell in CellDeps
+        let (spore_out_point, _) = build_spore_contract_materials(&mut context, "spore");
+        let referred_args = if referred_in_deps {
+            referred_id
+        } else {
+            [0xa5; 32]
+        };
+        let referred_type = build_spore_type_script(
+            &mut context,
+            &spore_out_point,
+            referred_args.to_vec().into(),
+        );
+        let referred_data =
+            build_serialized_spore_data("Hello Spore!".as_bytes().to_vec(), "plain/text", None);
+        let referred_dep =
+            build_normal_cell_dep(&mut context, referred_data.as_slice(), referred_type);
+
+        let tx = tx.as_advanced_builder().cell_dep(referred_dep).build();
+        let tx = context.complete_tx(tx);
+        context
+            .verify_tx(&tx, MAX_CYCLES)
+            .expect("test spore ref mint");
+    }
+
+    #[test]
+    fn test_spore_ref_mint() {
+        let content = r#"{"layers": [{"ref": "spore://{referred_id}", "z": 1}]}"#;
+        make_spore_ref_mint(content, "application/spore-ref+json;strict=true", true);
+        // referred spores are only checked in strict mode
+        make_spore_ref_mint(content, "application/spore-ref+json", false);
+    }
+
+    #[should_panic]
+    #[test]
+    fn test_spore_ref_mint_failed_without_referred_spore() {
+        let content = r#"{"layers": [{"ref": "spore://{referred_id}", "z": 1}]}"#;
+        make_spore_ref_mint(content, "application/spore-ref+json;strict=true", false);
+    }
+
+    #[should_panic]
+    #[test]
+    fn test_spore_ref_mint_failed_with_invalid_ref() {
+        let content = r#"{"layers": [{"ref": "spore://0x{referred_id}", "z": 1}]}"#;
+        make_spore_ref_mint(content, "application/spore-ref+json", true);
+    }
+}
+
 mod simple_spore_transfer {
     use super::*;
 
-    fn make_simple_spore_transfer(new_content: Vec<u8>, new_out_index: usize) {
+    fn make_simple_spore_transfer(content_type: &str, new_content: Vec<u8>, new_out_index: usize) {
         let mut context = Context::default();
         let (spore_out_point, spore_script_dep) =
             build_spore_contract_materials(&mut context, "spore");
@@ -205,14 +417,14 @@ mod simple_spore_transfer {
         // build spore cell in Input
         let old_spore_id = build_type_id(&normal_input, 0);
         let old_serialized =
-            build_serialized_spore_data("Hello Spore!".as_bytes().to_vec(), "plain/text", None);
+            build_serialized_spore_data("Hello Spore!".as_bytes().to_vec(), content_type, None);
         let old_spore_type =
             build_spore_type_script(&mut context, &spore_out_point, old_spore_id.to_vec().into());
         let spore_input = build_spore_input(&mut context, old_spore_type.clone(), old_serialized);
 
         // build spore cell in Output
         let new_spore_id = build_type_id(&normal_input, new_out_index);
-        let new_serialized = build_serialized_spore_data(new_content, "plain/text", None);
+        let new_serialized = build_serialized_spore_data(new_content, content_type, None);
         let new_spore_type =
             build_spore_type_script(&mut context, &spore_out_point, new_spore_id.to_vec().into());
         let spore_output = build_normal_output_cell_with_type(&mut context, new_spore_type.clone());
@@ -237,19 +449,25 @@ mod simple_spore_transfer {
 
     #[test]
     fn test_simple_spore_transfer() {
-        make_simple_spore_transfer("Hello Spore!".as_bytes().to_vec(), 0);
+        make_simple_spore_transfer("plain/text", "Hello Spore!".as_bytes().to_vec(), 0);
+    }
+
+    #[test]
+    fn test_simple_spore_transfer_with_legacy_content_type() {
+        // minted before the strict grammar, which rejects leading whitespaces
+        make_simple_spore_transfer(" image/png", "Hello Spore!".as_bytes().to_vec(), 0);
     }
 
     #[should_panic]
     #[test]
     fn test_simple_spore_transfer_failed_with_wrong_content() {
-        make_simple_spore_transfer("Hello New Spore!".as_bytes().to_vec(), 0);
+        make_simple_spore_transfer("plain/text", "Hello New Spore!".as_bytes().to_vec(), 0);
     }
 
     #[should_panic]
     #[test]
     fn test_simple_spore_transfer_failed_with_wrong_out_index() {
-        make_simple_spore_transfer("Hello Spore!".as_bytes().to_vec(), 1);
+        make_simple_spore_transfer("plain/text", "Hello Spore!".as_bytes().to_vec(), 1);
     }
 }
 
@@ -301,6 +519,229 @@ mod spore_mint_from_cluster_lock_proxy {
     }
 }
 
+mod spore_mint_from_cluster_with_allowlist {
+    use super::*;
+    use spore_utils::merkle::AllowlistTree;
+
+    fn make_spore_mint_from_cluster_with_allowlist(in_allowlist: bool, with_proof: bool) {
+        let mut context = Context::default();
+        let (cluster_out_point, _) = build_spore_contract_materials(&mut context, "cluster");
+
+        // build allowlist
+        let minter_lock_hash = build_default_lock_hash(&mut context);
+        let mut lock_hashes: Vec<[u8; 32]> = (1..5u8).map(|i| [i; 32]).collect();
+        if in_allowlist {
+            lock_hashes.push(minter_lock_hash);
+        }
+        let tree = AllowlistTree::new(&lock_hashes);
+        let proof = tree
+            .proof(&minter_lock_hash)
+            .unwrap_or_else(|| tree.proof(&[1u8; 32]).unwrap());
+
+        // build cluster celldep
+        let cluster = build_serialized_cluster_data_with_allowlist(
+            "Spore Cluster",
+            "Test Cluster",
+            tree.root(),
+        );
+        let cluster_id = blake2b_256("allowlist cluster");
+        let cluster_type =
+            build_spore_type_script(&mut context, &cluster_out_point, cluster_id.to_vec().into());
+        let cluster_dep = build_normal_cell_dep_with_lock_args(
+            &mut context,
+            cluster.as_slice(),
+            cluster_type,
+            &[],
+        );
+
+        // build spore mint from cluster tx
+        let tx = if with_proof {
+            build_single_spore_mint_tx_with_allowlist_proof(
+                &mut context,
+                "Hello Spore!".as_bytes().to_vec(),
+                "plain/text",
+                cluster_id,
+                proof,
+            )
+        } else {
+            build_single_spore_mint_tx(
+                &mut context,
+                "Hello Spore!".as_bytes().to_vec(),
+                "plain/text",
+                None,
+                Some(cluster_id),
+            )
+        };
+        let tx = tx.as_advanced_builder().cell_dep(cluster_dep).build();
+        let tx = context.complete_tx(tx);
+
+        context
+            .verify_tx(&tx, MAX_CYCLES)
+            .expect("test spore mint from cluster with allowlist");
+    }
+
+    #[test]
+    fn test_spore_mint_from_cluster_with_allowlist() {
+        make_spore_mint_from_cluster_with_allowlist(true, true);
+    }
+
+    #[should_panic]
+    #[test]
+    fn test_spore_mint_from_cluster_with_allowlist_failed_without_proof() {
+        make_spore_mint_from_cluster_with_allowlist(true, false);
+    }
+
+    #[should_panic]
+    #[test]
+    fn test_spore_mint_from_cluster_with_allowlist_failed_with_wrong_proof() {
+        make_spore_mint_from_cluster_with_allowlist(false, true);
+    }
+}
+
+mod spore_mint_from_cluster_with_public_mint {
+    use super::*;
+
+    const ABSOLUTE_EPOCH_SINCE_FLAG: u64 = 0x2000_0000_0000_0000;
+
+    fn make_spore_mint_from_cluster_with_public_mint(
+        since: Option<u64>,
+        price: u64,
+        payment: u64,
+        add_agent_dep: bool,
+    ) {
+        let mut context = Context::default();
+        let (cluster_out_point, _) = build_spore_contract_materials(&mut context, "cluster");
+        let (agent_out_point, _) = build_spore_contract_materials(&mut context, "cluster_agent");
+
+        // build cluster celldep, whose lock is different from the minter
+        let cluster = build_serialized_cluster_data_with_public_mint(
+            "Spore Cluster",
+            "Test Cluster",
+            10,
+            price,
+        );
+        let cluster_id = blake2b_256("public mint cluster");
+        let cluster_type =
+            build_spore_type_script(&mut context, &cluster_out_point, cluster_id.to_vec().into());
+        let cluster_dep = build_normal_cell_dep_with_lock_args(
+            &mut context,
+            cluster.as_slice(),
+            cluster_type,
+            &[1],
+        );
+
+        // build spore mint from cluster tx
+        let mut tx = build_single_spore_mint_tx(
+            &mut context,
+            "Hello Spore!".as_bytes().to_vec(),
+            "plain/text",
+            None,
+            Some(cluster_id),
+        )
+        .as_advanced_builder()
+        .cell_dep(cluster_dep)
+        .build();
+        if add_agent_dep {
+            // cluster agent whose lock is different from the minter either
+            let agent_type =
+                build_spore_type_script(&mut context, &agent_out_point, cluster_id.to_vec().into());
+            let agent_dep = build_normal_cell_dep_with_lock_args(
+                &mut context,
+                &blake2b_256("12345678"),
+                agent_type,
+                &[2],
+            );
+            tx = tx.as_advanced_builder().cell_dep(agent_dep).build();
+        }
+        if let Some(since) = since {
+            let since_input = build_normal_input_with_since(&mut context, since);
+            tx = tx.as_advanced_builder().input(since_input).build();
+        }
+        if payment > 0 {
+            let payment_output = build_normal_output_with_lock_args(&mut context, payment, &[1]);
+            tx = tx
+                .as_advanced_builder()
+                .output(payment_output)
+                .output_data(Default::default())
+                .build();
+        }
+        let tx = context.complete_tx(tx);
+
+        context
+            .verify_tx(&tx, MAX_CYCLES)
+            .expect("test spore mint from cluster with public mint");
+    }
+
+    #[test]
+    fn test_spore_mint_from_cluster_with_public_mint() {
+        make_spore_mint_from_cluster_with_public_mint(
+            Some(ABSOLUTE_EPOCH_SINCE_FLAG | 15),
+            0,
+            0,
+            false,
+        );
+    }
+
+    #[test]
+    fn test_spore_mint_from_cluster_with_paid_public_mint() {
+        make_spore_mint_from_cluster_with_public_mint(
+            Some(ABSOLUTE_EPOCH_SINCE_FLAG | 10),
+            100 * CAPACITY_UNIT,
+            100 * CAPACITY_UNIT,
+            false,
+        );
+    }
+
+    #[test]
+    fn test_spore_mint_from_cluster_with_public_mint_and_agent_in_dep() {
+        make_spore_mint_from_cluster_with_public_mint(
+            Some(ABSOLUTE_EPOCH_SINCE_FLAG | 15),
+            0,
+            0,
+            true,
+        );
+    }
+
+    #[should_panic]
+    #[test]
+    fn test_spore_mint_from_cluster_with_public_mint_failed_without_since() {
+        make_spore_mint_from_cluster_with_public_mint(None, 0, 0, false);
+    }
+
+    #[should_panic]
+    #[test]
+    fn test_spore_mint_from_cluster_with_public_mint_failed_before_start() {
+        make_spore_mint_from_cluster_with_public_mint(
+            Some(ABSOLUTE_EPOCH_SINCE_FLAG | 9),
+            0,
+            0,
+            false,
+        );
+    }
+
+    #[should_panic]
+    #[test]
+    fn test_spore_mint_from_cluster_with_public_mint_failed_with_payment_not_enough() {
+        make_spore_mint_from_cluster_with_public_mint(
+            Some(ABSOLUTE_EPOCH_SINCE_FLAG | 15),
+            100 * CAPACITY_UNIT,
+            99 * CAPACITY_UNIT,
+            false,
+        );
+    }
+
+    #[should_panic]
+    #[test]
+    fn test_spore_mint_from_cluster_with_public_mint_failed_with_price_overflow() {
+        make_spore_mint_from_cluster_with_public_mint(
+            Some(ABSOLUTE_EPOCH_SINCE_FLAG | 15),
+            u64::MAX,
+            100 * CAPACITY_UNIT,
+            false,
+        );
+    }
+}
+
 /// test creating Spore v2 with Cluster v1
 #[test]
 fn test_spore_mint_from_cluster_lock_proxy_compatible_v1() {
@@ -579,4 +1020,15 @@ mod simple_spore_destroy {
     fn test_simple_spore_destroy_with_immortal() {
         make_simple_spore_destroy("plain/text;immortal=true");
     }
+
+    #[test]
+    fn test_simple_spore_destroy_with_legacy_content_type() {
+        make_simple_spore_destroy(" image/png");
+    }
+
+    #[should_panic]
+    #[test]
+    fn test_simple_spore_destroy_with_legacy_immortal() {
+        make_simple_spore_destroy(" image/png; immortal=true");
+    }
 }
@@ -1,6 +1,31 @@
-use ckb_testtool::ckb_types::prelude::{Builder, Entity};
-use spore_types::generated::spore;
-use spore_utils::{compatible_load_cluster_data, MIME};
+use ckb_testtool::ckb_hash::blake2b_256;
+use ckb_testtool::ckb_types::bytes::Bytes;
+use ckb_testtool::ckb_types::core::ScriptHashType;
+use ckb_testtool::ckb_types::packed::{self, Byte};
+use ckb_testtool::ckb_types::prelude::{Builder, Entity, Pack};
+use spore_types::generated::hashref::ContentHashRef;
+use spore_types::generated::{action, spore};
+use spore_types::hashref::{
+    ContentStore, HashAlgorithm, HashRefError, LocalFileStore, NativeContentHashRef,
+    HASHREF_CONTENT_TYPE,
+};
+use spore_types::native::{
+    NativeAddress, NativeBurnSpore, NativeClusterDataV2, NativeClusterDataV3,
+    NativeMintSporeWithProof, NativePublicMint, NativeScript, NativeSporeAction, NativeSporeData,
+    NativeTransferSpore,
+};
+use spore_types::NativeNFTData;
+use spore_utils::lua::{check_ckb_api_usage, decode_mutant_error, MutantError};
+use spore_utils::merkle::{verify_allowlist_proof, AllowlistTree};
+use spore_utils::{
+    check_image_header, compatible_load_cluster_data, decode_content, decode_nft_content,
+    encode_content, encode_nft_content, extract_multipart_parts, extract_spore_refs,
+    is_relative_since_satisfied, is_spore_ref_content, parse_absolute_epoch_since, parse_multipart,
+    validate_content, validate_hashref, validate_json, ContentEncoding, Mime,
+    MAX_DECODED_CONTENT_LEN, MIME,
+};
+
+use crate::utils::build_serialized_mutant_data;
 
 #[test]
 fn test_mime_basic() {
@@ -31,6 +56,578 @@ fn test_mime_basic() {
         .unwrap()
         .expect("empty range");
     assert!(content_type.as_bytes()[value_range] == expected_value[..]);
+
+    let content_type = "image/png;mutant[]=c219351b150b900e50a7039f1e448b844110927e5fd9bd30425806cb8ddff1fd,9c87faf08de5c15c727d5350399115431bf4f0226fbc4abd400e63492faac3d2;mutant_args[]=\"edition-5, rare\"";
+    let mime = MIME::str_parse(content_type)
+        .map_err(|err| format!("mutant_args str_parse: {}", err as u8))
+        .unwrap();
+    assert_eq!(mime.mutant_args.len(), 2);
+    assert_eq!(&content_type[mime.mutant_args[0].clone()], "edition-5");
+    assert_eq!(&content_type[mime.mutant_args[1].clone()], "rare");
+    assert!(MIME::str_parse("image/png;mutant[]=c219351b150b900e50a7039f1e448b844110927e5fd9bd30425806cb8ddff1fd;mutant_args[]=a,b").is_err());
+
+    let content_type = "image/png;mutant[]=\"c219351b150b900e50a7039f1e448b844110927e5fd9bd30425806cb8ddff1fd@2, 9c87faf08de5c15c727d5350399115431bf4f0226fbc4abd400e63492faac3d2\"";
+    let mime = MIME::str_parse(content_type)
+        .map_err(|err| format!("mutant version str_parse: {}", err as u8))
+        .unwrap();
+    assert_eq!(mime.mutants.len(), 2);
+    assert_eq!(mime.mutant_versions, vec![Some(2), None]);
+    assert!(MIME::str_parse("image/png;mutant[]=\"c219351b150b900e50a7039f1e448b844110927e5fd9bd30425806cb8ddff1fd@latest\"").is_err());
+}
+
+// fixture files list a content type per line, lines starting with '#' are comments
+fn load_mime_fixtures(name: &str) -> Vec<String> {
+    std::fs::read_to_string(format!("resource/mime/{name}"))
+        .expect("read mime fixtures")
+        .lines()
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_owned)
+        .collect()
+}
+
+#[test]
+fn test_mime_fixtures() {
+    for content_type in load_mime_fixtures("valid.txt") {
+        if let Err(err) = MIME::parse(content_type.as_bytes()) {
+            panic!(
+                "valid content type {content_type:?} rejected: {}",
+                err as u8
+            );
+        }
+    }
+    for content_type in load_mime_fixtures("invalid.txt") {
+        if MIME::parse(content_type.as_bytes()).is_ok() {
+            panic!("invalid content type {content_type:?} accepted");
+        }
+    }
+}
+
+#[test]
+fn test_mime_lenient_fixtures() {
+    // lenient parsing agrees with the strict one on content types of the strict grammar
+    for content_type in load_mime_fixtures("valid.txt") {
+        let strict = MIME::parse(content_type.as_bytes())
+            .map_err(|err| err as u8)
+            .unwrap();
+        let lenient = MIME::parse_lenient(content_type.as_bytes())
+            .map_err(|err| err as u8)
+            .unwrap();
+        assert_eq!(
+            format!("{strict:?}"),
+            format!("{lenient:?}"),
+            "{content_type:?}"
+        );
+    }
+    // and accepts legacy ones, which are rejected on mint
+    let mutant_id = "c219351b150b900e50a7039f1e448b844110927e5fd9bd30425806cb8ddff1fd";
+    for content_type in [
+        " image/png".to_owned(),
+        "image/png; immortal = true".to_owned(),
+        format!(" image/png;mutant[]={mutant_id};bad param;key=\"unterminated"),
+    ] {
+        assert!(MIME::parse(content_type.as_bytes()).is_err());
+        let mime = MIME::parse_lenient(content_type.as_bytes())
+            .map_err(|err| format!("{content_type:?}: {}", err as u8))
+            .unwrap();
+        assert_eq!(&content_type[mime.main_type.clone()], "image");
+        assert_eq!(&content_type[mime.sub_type.clone()], "png");
+    }
+    let mime = MIME::parse_lenient(b"image/png; immortal = true").unwrap();
+    assert!(mime.immortal);
+    let content_type = format!(" image/png;mutant[]={mutant_id};bad param");
+    let mime = MIME::parse_lenient(content_type.as_bytes()).unwrap();
+    assert_eq!(mime.mutants.len(), 1);
+    // mutants are still checked, since they are committed on mint
+    assert!(MIME::parse_lenient(b" image/png;mutant[]=c219").is_err());
+}
+
+#[test]
+fn test_mime_param_value() {
+    let content_type = "Multipart/Mixed; BOUNDARY=\"Spore \\\"Boundary\\\"\"; charset=utf-8";
+    let mime = MIME::str_parse(content_type)
+        .map_err(|err| err as u8)
+        .unwrap();
+    assert_eq!(&content_type[mime.main_type.clone()], "Multipart");
+    let boundary = mime
+        .get_param_value(content_type.as_bytes(), "boundary")
+        .map_err(|err| err as u8)
+        .unwrap()
+        .expect("boundary param");
+    assert_eq!(boundary.as_ref(), b"Spore \"Boundary\"");
+    let charset = mime
+        .get_param_value(content_type.as_bytes(), "Charset")
+        .map_err(|err| err as u8)
+        .unwrap()
+        .expect("charset param");
+    assert_eq!(charset.as_ref(), b"utf-8");
+    assert!(mime
+        .get_param_value(content_type.as_bytes(), "format")
+        .map_err(|err| err as u8)
+        .unwrap()
+        .is_none());
+}
+
+#[test]
+fn test_owned_mime_round_trip() {
+    let mime = Mime::new("Image", "PNG")
+        .param("Charset", "utf-8")
+        .param("title", "a \"quoted\" title")
+        .immortal(true)
+        .pinned_mutant([0xab; 32], 2)
+        .mutant_args("edition-5")
+        .mutant([0x01; 32])
+        .mutant_args("rare");
+    let content_type = mime.to_bytes().map_err(|err| err as u8).unwrap();
+    assert_eq!(
+        String::from_utf8(content_type.clone()).unwrap(),
+        format!(
+            "image/png;charset=utf-8;title=\"a \\\"quoted\\\" title\";immortal=true;mutant[]=\"{}@2,{}\";mutant_args[]=\"edition-5,rare\"",
+            hex::encode([0xab; 32]),
+            hex::encode([0x01; 32])
+        )
+    );
+    assert!(MIME::parse(&content_type).is_ok());
+    assert_eq!(
+        Mime::parse(&content_type).map_err(|err| err as u8).unwrap(),
+        mime
+    );
+
+    let mime = "TEXT/Plain; Format=\"flowed\"; immortal=false"
+        .parse::<Mime>()
+        .map_err(|err| err as u8)
+        .unwrap();
+    assert_eq!(mime.to_string(), "text/plain;format=flowed");
+    assert_eq!(mime.get_param("FORMAT"), Some("flowed"));
+
+    // content types that contract rejects can't be serialized
+    assert!(Mime::new("te xt", "plain").to_bytes().is_err());
+    assert!(Mime::new("text", "plain")
+        .mutant([0x01; 32])
+        .mutant_args("a,b")
+        .to_bytes()
+        .is_err());
+}
+
+#[test]
+fn test_owned_mime_round_trip_with_non_token_values() {
+    for value in [
+        "",
+        "two words",
+        "a;b=c",
+        "path/to",
+        "a,b",
+        "back\\slash",
+        "\"quoted\"",
+        "tab\tseparated",
+        "[brackets]",
+        "ünïcode",
+    ] {
+        let mime = Mime::new("text", "plain").param("title", value);
+        let content_type = mime.to_string();
+        assert!(
+            content_type.starts_with("text/plain;title=\""),
+            "{content_type:?}"
+        );
+        let parsed = Mime::parse(content_type.as_bytes())
+            .map_err(|err| format!("{content_type:?}: {}", err as u8))
+            .unwrap();
+        assert_eq!(parsed.get_param("title"), Some(value));
+        assert_eq!(parsed, mime);
+    }
+}
+
+#[test]
+fn test_validate_content() {
+    let validate = |content_type: &str, content: &[u8]| {
+        let mime = MIME::str_parse(content_type).map_err(|err| err as u8)?;
+        validate_content(content_type.as_bytes(), &mime, content).map_err(|err| err as u8)
+    };
+    assert!(validate("text/plain;charset=utf-8", "héllo".as_bytes()).is_ok());
+    assert!(validate("text/plain;charset=utf-8", b"\xc3").is_err());
+    assert!(validate("text/plain;charset=us-ascii", "héllo".as_bytes()).is_err());
+    assert!(validate("text/plain;charset=iso-8859-1", b"\xe9").is_ok());
+    assert!(validate("application/json", b" [1, -0.5e+3, {\"a\": \"\\u00e9\"}] ").is_ok());
+    assert!(validate("application/geo+json", b"{\"a\": 1,}").is_err());
+    assert!(validate("image/png", b"{").is_ok());
+
+    // encoded content is decoded whenever validation is asked, and stays opaque otherwise
+    let gzip = |content: &[u8]| encode_content(ContentEncoding::Gzip, content);
+    assert!(validate("application/json;encoding=gzip", &gzip(b"{\"a\": 1}")).is_ok());
+    assert!(validate("application/json;encoding=gzip", &gzip(b"{\"a\": 1,}")).is_err());
+    assert!(validate("text/plain;charset=utf-8;encoding=gzip", &gzip(b"\xc3")).is_err());
+    assert!(validate("text/plain;encoding=gzip", b"not gzip").is_ok());
+    assert!(validate("text/plain;encoding=gzip;strict=true", b"not gzip").is_err());
+    // decoded content is handed back only if it has been decoded for validation
+    assert_eq!(
+        validate("application/json;encoding=gzip", &gzip(b"[]")),
+        Ok(Some(b"[]".to_vec()))
+    );
+    assert_eq!(validate("text/plain;encoding=gzip", b"not gzip"), Ok(None));
+    assert_eq!(validate("application/json", b"[]"), Ok(None));
+
+    for invalid in [
+        "", "{", "[1 2]", "01", "1.", "tru", "\"\\x\"", "{} {}", "{1: 2}",
+    ] {
+        assert!(validate_json(invalid.as_bytes()).is_err(), "{invalid}");
+    }
+    let nested = format!("{}{}", "[".repeat(10000), "]".repeat(10000));
+    assert!(validate_json(nested.as_bytes()).is_ok());
+}
+
+#[test]
+fn test_check_image_header() {
+    let mut png = b"\x89PNG\r\n\x1a\n\0\0\0\x0dIHDR\0\0\0\x01\0\0\0\x01\x08\x06\0\0\0".to_vec();
+    png.extend_from_slice(b"\0\0\0\0\0\0\0\0IEND\xae\x42\x60\x82");
+    assert!(check_image_header(b"png", &png).is_ok());
+    assert!(check_image_header(b"png", &png[..png.len() - 12]).is_err());
+
+    let jpeg = std::fs::read("resource/test.jpg").unwrap();
+    assert!(check_image_header(b"jpeg", &jpeg).is_ok());
+    assert!(check_image_header(b"gif", &jpeg).is_err());
+
+    assert!(check_image_header(b"gif", b"GIF89a\x01\0\x01\0\0\0\0\x3b").is_ok());
+    assert!(check_image_header(b"gif", b"GIF89a\0\0\x01\0\0\0\0\x3b").is_err());
+    assert!(check_image_header(b"webp", b"RIFF\x0c\0\0\0WEBPVP8L\0\0\0\0").is_ok());
+    assert!(check_image_header(b"webp", b"RIFF\x10\0\0\0WEBPVP8L\0\0\0\0").is_err());
+
+    let svg = b"<?xml version=\"1.0\"?>\n<!DOCTYPE svg>\n<svg xmlns=\"http://www.w3.org/2000/svg\"></svg>\n";
+    assert!(check_image_header(b"svg+xml", svg).is_ok());
+    assert!(check_image_header(b"svg+xml", b"<html></html>").is_err());
+    // unsupported image types can't be verified
+    assert!(check_image_header(b"bmp", b"BM").is_err());
+}
+
+#[test]
+fn test_content_encoding() {
+    let content = r#"{"name": "Spore", "description": "compressed spore"}"#.repeat(32);
+    for encoding in [
+        ContentEncoding::Gzip,
+        ContentEncoding::Zstd,
+        ContentEncoding::Brotli,
+    ] {
+        let encoded = encode_content(encoding, content.as_bytes());
+        assert!(encoded.len() < content.len());
+        let decoded = decode_content(encoding, &encoded, MAX_DECODED_CONTENT_LEN)
+            .map_err(|err| err as u8)
+            .unwrap();
+        assert_eq!(decoded, content.as_bytes());
+        // decoded content is limited
+        assert!(decode_content(encoding, &encoded, content.len() - 1).is_err());
+        assert!(decode_content(encoding, &encoded[..encoded.len() - 1], content.len()).is_err());
+    }
+    let mime = MIME::str_parse("text/plain;encoding=ZSTD")
+        .map_err(|err| err as u8)
+        .unwrap();
+    assert_eq!(mime.encoding, Some(ContentEncoding::Zstd));
+    assert!(MIME::str_parse("text/plain;encoding=lzma").is_err());
+
+    let nft = NativeNFTData {
+        content_type: "application/json".to_owned(),
+        content: content.clone().into_bytes(),
+        cluster_id: None,
+    };
+    let encoded = encode_nft_content(nft.clone(), ContentEncoding::Gzip)
+        .map_err(|err| err as u8)
+        .unwrap();
+    assert_eq!(encoded.content_type, "application/json;encoding=gzip");
+    assert_eq!(
+        decode_nft_content(&encoded)
+            .map_err(|err| err as u8)
+            .unwrap(),
+        nft.content
+    );
+    assert_eq!(
+        decode_nft_content(&nft).map_err(|err| err as u8).unwrap(),
+        nft.content
+    );
+    // content can't be encoded twice
+    assert!(encode_nft_content(encoded, ContentEncoding::Zstd).is_err());
+}
+
+#[test]
+fn test_extract_spore_refs() {
+    let (first, second) = ([0x01; 32], [0xab; 32]);
+    let content = format!(
+        r#"{{"name": "spore://not-a-ref", "layers": [{{"ref": "spore://{}"}}, "spore://{}"]}}"#,
+        hex::encode(first),
+        hex::encode_upper(second)
+    );
+    // object keys are not references
+    assert!(extract_spore_refs(content.as_bytes()).is_err());
+    let content = content.replace(r#""name": "spore://not-a-ref""#, r#""spore://key": 1"#);
+    let refs = extract_spore_refs(content.as_bytes())
+        .map_err(|err| err as u8)
+        .unwrap();
+    assert_eq!(refs, vec![first, second]);
+
+    // at least one reference is required
+    assert!(extract_spore_refs(br#"{"layers": []}"#).is_err());
+    assert!(extract_spore_refs(br#"["spore://1234"]"#).is_err());
+
+    // references are matched after escapes in strings are resolved
+    let escaped = format!(
+        r#"["spore:\/\/{}"]"#,
+        hex::encode(first).replacen('0', r"\u0030", 1)
+    );
+    let refs = extract_spore_refs(escaped.as_bytes())
+        .map_err(|err| err as u8)
+        .unwrap();
+    assert_eq!(refs, vec![first]);
+    assert!(extract_spore_refs(br#"["spore:\/\/\ud83c\udf44"]"#).is_err());
+
+    let content_type = "Application/Spore-Ref+JSON;strict=true";
+    let mime = MIME::str_parse(content_type)
+        .map_err(|err| err as u8)
+        .unwrap();
+    assert!(is_spore_ref_content(content_type.as_bytes(), &mime));
+    assert!(validate_content(content_type.as_bytes(), &mime, content.as_bytes()).is_ok());
+    assert!(validate_content(content_type.as_bytes(), &mime, br#"{"ref": 1}"#).is_err());
+}
+
+#[test]
+fn test_content_hashref() {
+    let jpeg = std::fs::read("resource/test.jpg").unwrap();
+    let hashref = NativeContentHashRef::new(
+        HashAlgorithm::Blake2b256,
+        &jpeg,
+        vec!["ipfs://missing".to_owned(), "test.jpg".to_owned()],
+    );
+    assert_eq!(hashref.hash, blake2b_256(&jpeg));
+    let content: ContentHashRef = hashref.clone().into();
+    assert_eq!(
+        NativeContentHashRef::from_slice(content.as_slice()),
+        Some(hashref.clone())
+    );
+    let mime = MIME::str_parse(HASHREF_CONTENT_TYPE)
+        .map_err(|err| err as u8)
+        .unwrap();
+    assert!(validate_content(HASHREF_CONTENT_TYPE.as_bytes(), &mime, content.as_slice()).is_ok());
+
+    // content is fetched from the first available URI
+    let store = LocalFileStore::new("resource");
+    assert_eq!(hashref.verify(&store), Ok(jpeg.clone()));
+    let sha256 = NativeContentHashRef::new(HashAlgorithm::Sha256, &jpeg, hashref.uris.clone());
+    assert_eq!(sha256.verify(&store), Ok(jpeg));
+    let mismatched = NativeContentHashRef {
+        size: hashref.size - 1,
+        ..hashref.clone()
+    };
+    assert_eq!(
+        mismatched.verify(&store),
+        Err(HashRefError::ContentMismatch)
+    );
+    let missing = NativeContentHashRef {
+        uris: vec!["ipfs://missing".to_owned()],
+        ..hashref.clone()
+    };
+    assert_eq!(missing.verify(&store), Err(HashRefError::ContentNotFound));
+
+    // relative URIs can't escape the root of store
+    let store = LocalFileStore::new("resource/mime");
+    assert!(store.fetch("valid.txt").is_some());
+    assert!(store.fetch("./valid.txt").is_some());
+    assert_eq!(store.fetch("../test.jpg"), None);
+    assert_eq!(store.fetch("mime/../../test.jpg"), None);
+    let absolute = std::fs::canonicalize("resource/test.jpg").unwrap();
+    assert_eq!(store.fetch(absolute.to_str().unwrap()), None);
+    assert!(store
+        .fetch(&format!("file://{}", absolute.display()))
+        .is_some());
+
+    // commitment requires non-zero size and at least one non-empty URI
+    for invalid in [
+        NativeContentHashRef {
+            size: 0,
+            ..hashref.clone()
+        },
+        NativeContentHashRef {
+            uris: vec![],
+            ..hashref.clone()
+        },
+        NativeContentHashRef {
+            uris: vec![String::new()],
+            ..hashref.clone()
+        },
+    ] {
+        let content: ContentHashRef = invalid.into();
+        assert!(validate_hashref(content.as_slice()).is_err());
+    }
+    let unknown_algorithm = content.as_builder().algorithm(Byte::new(2)).build();
+    assert!(validate_hashref(unknown_algorithm.as_slice()).is_err());
+    assert!(validate_hashref(b"ipfs://missing").is_err());
+}
+
+#[test]
+fn test_extract_multipart_parts() {
+    let content_type = b"multipart/mixed; boundary=\"Spore Boundary\"";
+    let content = b"preamble\r\n--Spore Boundary\r\nContent-Type: image/png\r\nX-Title: cover\r\n\r\n\x89PNG\r\n--Spore Boundary \r\n\r\nplain text\n--Spore Boundary--\r\nepilogue";
+    let parts = extract_multipart_parts(content_type, content)
+        .map_err(|err| err as u8)
+        .unwrap();
+    assert_eq!(parts.len(), 2);
+    assert_eq!(parts[0].content_type, Some(b"image/png".as_slice()));
+    assert_eq!(
+        parts[0].headers,
+        b"Content-Type: image/png\r\nX-Title: cover\r\n"
+    );
+    assert_eq!(parts[0].body, b"\x89PNG");
+    assert_eq!(parts[1].content_type, None);
+    assert_eq!(parts[1].body, b"plain text");
+
+    // close delimiter is required
+    assert!(parse_multipart(b"--b\n\npart\n--b\n", b"b", None).is_err());
+    // content type of parts must be valid
+    assert!(parse_multipart(b"--b\nContent-Type: image\n\npart\n--b--", b"b", None).is_err());
+    assert!(parse_multipart(b"--b\n\npart\n--b\n\npart\n--b--", b"b", Some(1)).is_err());
+    assert!(extract_multipart_parts(b"text/plain", content).is_err());
+}
+
+#[test]
+fn test_native_types_json_round_trip() {
+    let spore_data = NativeSporeData {
+        content_type: b"text/plain".to_vec(),
+        content: vec![0xff, 0x00],
+        cluster_id: None,
+    };
+    let molecule_data: spore::SporeData = spore_data.clone().into();
+    assert_eq!(NativeSporeData::from(molecule_data), spore_data);
+    let json = serde_json::to_value(&spore_data).unwrap();
+    assert_eq!(
+        json,
+        serde_json::json!({"content_type": "0x746578742f706c61696e", "content": "0xff00", "cluster_id": null})
+    );
+    assert_eq!(
+        serde_json::from_value::<NativeSporeData>(json).unwrap(),
+        spore_data
+    );
+
+    let cluster_data = NativeClusterDataV2 {
+        name: b"Spore Cluster".to_vec(),
+        description: vec![],
+        mutant_id: Some(vec![0x01; 32]),
+    };
+    let molecule_data: spore::ClusterDataV2 = cluster_data.clone().into();
+    assert_eq!(NativeClusterDataV2::from(molecule_data), cluster_data);
+
+    let address = |byte: u8| {
+        NativeAddress::Script(NativeScript {
+            code_hash: [byte; 32],
+            hash_type: 1,
+            args: vec![byte; 20],
+        })
+    };
+    let transfer = NativeSporeAction::TransferSpore(NativeTransferSpore {
+        spore_id: [0x02; 32],
+        from: address(0x03),
+        to: address(0x04),
+    });
+    let molecule_action: action::SporeAction = transfer.clone().into();
+    assert_eq!(NativeSporeAction::from(molecule_action), transfer);
+    let json = serde_json::to_string(&transfer).unwrap();
+    assert!(json.starts_with(r#"{"TransferSpore":{"spore_id":"0x0202"#));
+    assert_eq!(
+        serde_json::from_str::<NativeSporeAction>(&json).unwrap(),
+        transfer
+    );
+
+    // allowlist proof is kept in its own action
+    let mint = NativeMintSporeWithProof {
+        spore_id: [0x05; 32],
+        to: address(0x06),
+        data_hash: [0x07; 32],
+        allowlist_proof: vec![[0x08; 32], [0x09; 32]],
+    };
+    let molecule_action: action::SporeAction =
+        NativeSporeAction::MintSporeWithProof(mint.clone()).into();
+    assert_eq!(
+        NativeSporeAction::from(molecule_action),
+        NativeSporeAction::MintSporeWithProof(mint.clone())
+    );
+    let json = serde_json::to_string(&mint).unwrap();
+    assert_eq!(
+        serde_json::from_str::<NativeMintSporeWithProof>(&json).unwrap(),
+        mint
+    );
+
+    // hex must be of the exact length
+    let json = json.replacen("0x0505", "0x05", 1);
+    assert!(serde_json::from_str::<NativeMintSporeWithProof>(&json).is_err());
+}
+
+#[test]
+fn test_native_types_conversions() {
+    // lock scripts convert to addresses and back losslessly
+    let script = packed::Script::new_builder()
+        .code_hash([0x01; 32].pack())
+        .hash_type(ScriptHashType::Type.into())
+        .args(Bytes::from(vec![0x02; 20]).pack())
+        .build();
+    let address = NativeAddress::from(script.clone());
+    let NativeAddress::Script(native_script) = address.clone();
+    assert_eq!(native_script.code_hash, [0x01; 32]);
+    assert_eq!(native_script.hash_type, 1);
+    let molecule_address = action::Address::from(script.clone());
+    assert_eq!(NativeAddress::from(molecule_address.clone()), address);
+    assert_eq!(
+        packed::Script::from(molecule_address).calc_script_hash(),
+        script.calc_script_hash()
+    );
+    assert_eq!(packed::Script::from(address), script);
+
+    // actions are parsed from raw bytes of either the union or its item
+    let burn = NativeBurnSpore {
+        spore_id: [0x03; 32],
+        from: script.into(),
+    };
+    let molecule_action: action::SporeAction = NativeSporeAction::from(burn.clone()).into();
+    assert_eq!(
+        NativeSporeAction::try_from(molecule_action.as_slice()).ok(),
+        Some(NativeSporeAction::BurnSpore(burn.clone()))
+    );
+    let molecule_burn: action::BurnSpore = burn.clone().into();
+    assert_eq!(
+        NativeBurnSpore::try_from(molecule_burn.as_slice()).ok(),
+        Some(burn)
+    );
+    assert!(NativeBurnSpore::try_from([0u8; 4].as_slice()).is_err());
+
+    // the latest cluster data is still readable as older versions
+    let cluster_data = NativeClusterDataV3 {
+        allowlist_root: Some(vec![0x04; 32]),
+        public_mint: Some(NativePublicMint {
+            start_epoch: 10,
+            price: 1000,
+        }),
+        ..NativeClusterDataV3::new("Spore Cluster", "Test")
+    };
+    let molecule_data: spore::ClusterDataV3 = cluster_data.clone().into();
+    assert_eq!(
+        NativeClusterDataV3::try_from(molecule_data.as_slice()).ok(),
+        Some(cluster_data)
+    );
+    assert_eq!(
+        NativeClusterDataV2::try_from(molecule_data.as_slice()).ok(),
+        Some(NativeClusterDataV2::new("Spore Cluster", "Test"))
+    );
+
+    // spore data converts to NFT data only if its content type is in UTF-8
+    let nft = NativeNFTData {
+        content_type: "text/plain".to_owned(),
+        content: b"content".to_vec(),
+        cluster_id: Some(vec![0x05; 32]),
+    };
+    let spore_data = NativeSporeData::from(nft.clone());
+    assert_eq!(spore_data.content_type, b"text/plain");
+    let nft = NativeNFTData::try_from(spore_data).unwrap();
+    assert_eq!(nft.content_type, "text/plain");
+    let invalid = NativeSporeData {
+        content_type: vec![0xff],
+        ..NativeSporeData::from(nft)
+    };
+    assert_eq!(
+        NativeNFTData::try_from(invalid.clone()).unwrap_err(),
+        invalid
+    );
 }
 
 #[test]
@@ -71,3 +668,153 @@ fn test_compatible_load_cluster_data() {
         .expect("test ClusterDataV2 -> ClusterDataV2");
     assert!(cluster_data_v2.mutant_id().is_some());
 }
+
+#[test]
+fn test_allowlist_merkle_proof() {
+    let lock_hashes: Vec<[u8; 32]> = (0..7u8).map(|i| [i; 32]).collect();
+    let tree = AllowlistTree::new(&lock_hashes);
+    let root = tree.root();
+    for lock_hash in &lock_hashes {
+        let proof = tree.proof(lock_hash).expect("proof of allowed lock hash");
+        assert!(verify_allowlist_proof(&root, lock_hash, &proof));
+    }
+
+    // lock hash out of allowlist
+    assert!(tree.proof(&[7u8; 32]).is_none());
+    let proof = tree.proof(&[0u8; 32]).unwrap();
+    assert!(!verify_allowlist_proof(&root, &[7u8; 32], &proof));
+
+    // tree is independent from the order of lock hashes
+    let reversed: Vec<[u8; 32]> = lock_hashes.iter().rev().cloned().collect();
+    assert_eq!(AllowlistTree::new(&reversed).root(), root);
+
+    // single leaf tree has an empty proof
+    let tree = AllowlistTree::new(&[[1u8; 32]]);
+    assert!(tree.proof(&[1u8; 32]).unwrap().is_empty());
+    assert!(verify_allowlist_proof(&tree.root(), &[1u8; 32], &[]));
+}
+
+#[test]
+fn test_compatible_load_cluster_data_with_allowlist() {
+    let cluster_data_v3 = spore::ClusterDataV3::new_builder()
+        .name("Test Cluster Name".as_bytes().into())
+        .description("Test Cluster Description".as_bytes().into())
+        .allowlist_root([1u8; 32].as_slice().into())
+        .build();
+    let cluster_data = compatible_load_cluster_data(cluster_data_v3.as_slice())
+        .map_err(|_| "compatible_load_cluster_data error")
+        .expect("test ClusterDataV3 -> ClusterDataV3");
+    assert!(cluster_data.mutant_id().is_none());
+    assert_eq!(
+        cluster_data.allowlist_root().to_opt().unwrap().raw_data(),
+        [1u8; 32].as_slice()
+    );
+
+    // ClusterDataV2 is still readable as ClusterDataV2
+    let cluster_data_v2 = spore::ClusterDataV2::from_compatible_slice(cluster_data_v3.as_slice())
+        .map_err(|_| "spore::ClusterDataV2::from_compatible_slice error")
+        .expect("test new format -> old format");
+    assert_eq!(cluster_data_v2.count_extra_fields(), 1);
+}
+
+#[test]
+fn test_parse_absolute_epoch_since() {
+    // epoch 15 with fraction 1/2
+    let since = 0x2000_0000_0000_0000u64 | (2 << 40) | (1 << 24) | 15;
+    assert_eq!(parse_absolute_epoch_since(since), Some(15));
+    // relative epoch
+    assert_eq!(parse_absolute_epoch_since(0xA000_0000_0000_000F), None);
+    // absolute block number
+    assert_eq!(parse_absolute_epoch_since(15), None);
+}
+
+#[test]
+fn test_relative_since_satisfied() {
+    // relative block number
+    assert!(is_relative_since_satisfied(
+        0x8000_0000_0000_0064,
+        0x8000_0000_0000_0064
+    ));
+    assert!(!is_relative_since_satisfied(
+        0x8000_0000_0000_0063,
+        0x8000_0000_0000_0064
+    ));
+    // relative epoch, 2 + 1/2 against 2 + 1/3
+    let epoch = |number: u64, index: u64, length: u64| {
+        0xA000_0000_0000_0000 | (length << 40) | (index << 24) | number
+    };
+    assert!(is_relative_since_satisfied(epoch(2, 1, 2), epoch(2, 1, 3)));
+    assert!(!is_relative_since_satisfied(epoch(2, 1, 3), epoch(2, 1, 2)));
+    // absolute since or another metric never satisfies
+    assert!(!is_relative_since_satisfied(
+        0x0000_0000_0000_0064,
+        0x8000_0000_0000_0064
+    ));
+    assert!(!is_relative_since_satisfied(
+        0xC000_0000_0000_0064,
+        0x8000_0000_0000_0064
+    ));
+}
+
+#[test]
+fn test_check_ckb_api_usage() {
+    let code = "
+        local data = ckb.load_cell_data(0, ckb.SOURCE_OUTPUT)
+        -- ckb.spawn in comments is fine
+        local text = 'ckb.spawn in strings is fine' .. [[ckb.mount]]
+        --[==[ ckb.exit ]==]
+        if data == nil then ckb . exit_script(1) end
+    ";
+    assert!(check_ckb_api_usage(code.as_bytes()).is_ok());
+
+    assert!(check_ckb_api_usage(b"ckb.spawn_cell()").is_err());
+    assert!(check_ckb_api_usage(b"ckb['spawn_cell']()").is_err());
+    assert!(check_ckb_api_usage(b"local c = ckb; c.spawn_cell()").is_err());
+    assert!(check_ckb_api_usage(b"_G.ckb.spawn_cell()").is_err());
+    assert!(check_ckb_api_usage(b"print('unclosed)").is_err());
+}
+
+#[test]
+fn test_decode_mutant_error() {
+    let errors: [(u8, &[u8]); 2] = [
+        (0, "unknown edition".as_bytes()),
+        (3, "transfer is locked".as_bytes()),
+    ];
+    let code = "if spore_ext_args == nil then spore_error(0) end";
+    let mutant_data = build_serialized_mutant_data("Edition".as_bytes(), None, &errors, code);
+    let mutant_data = mutant_data.as_slice();
+    assert_eq!(
+        decode_mutant_error(113, mutant_data),
+        Some(MutantError {
+            code: 3,
+            message: Some("transfer is locked")
+        })
+    );
+    assert_eq!(
+        decode_mutant_error(110, mutant_data),
+        Some(MutantError {
+            code: 0,
+            message: Some("unknown edition")
+        })
+    );
+    // undeclared code and raw Lua code are decoded without message
+    assert_eq!(
+        decode_mutant_error(114, mutant_data),
+        Some(MutantError {
+            code: 4,
+            message: None
+        })
+    );
+    assert_eq!(
+        decode_mutant_error(110, code.as_bytes()),
+        Some(MutantError {
+            code: 0,
+            message: None
+        })
+    );
+    // failures of Lua VM and codes reserved for validation are not application errors
+    assert_eq!(decode_mutant_error(-1, mutant_data), None);
+    assert_eq!(decode_mutant_error(21, mutant_data), None);
+    assert_eq!(decode_mutant_error(103, mutant_data), None);
+    assert_eq!(decode_mutant_error(126, mutant_data), None);
+}
@@ -4,12 +4,11 @@ use ckb_testtool::ckb_types::packed;
 use ckb_testtool::ckb_types::prelude::*;
 
 use ckb_testtool::context::Context;
-use spore_types::generated::action::BurnAgent;
-use spore_types::generated::action::BurnProxy;
-use spore_types::generated::action::{
-    Address, AddressUnion, BurnSpore, Byte32, Bytes, MintAgent, MintCluster, MintProxy, MintSpore,
-    Script, SporeAction, SporeActionUnion, TransferAgent, TransferCluster, TransferProxy,
-    TransferSpore,
+use spore_types::generated::action::{SporeAction, SporeActionUnion};
+use spore_types::native::{
+    NativeBurnAgent, NativeBurnProxy, NativeBurnSpore, NativeMintAgent, NativeMintCluster,
+    NativeMintProxy, NativeMintSpore, NativeMintSporeWithProof, NativeTransferAgent,
+    NativeTransferCluster, NativeTransferProxy, NativeTransferSpore,
 };
 use spore_utils::co_build_types::{
     Action, ActionVec, Message, SighashAll, WitnessLayout, WitnessLayoutUnion,
@@ -17,37 +16,6 @@ use spore_utils::co_build_types::{
 
 use super::internal;
 
-fn h256_to_byte32(hash: [u8; 32]) -> Byte32 {
-    let hash = hash
-        .into_iter()
-        .map(packed::Byte::new)
-        .collect::<Vec<packed::Byte>>()
-        .try_into()
-        .unwrap();
-    Byte32::new_builder().set(hash).build()
-}
-
-fn script_to_address(script: packed::Script) -> Address {
-    let code_hash = script.code_hash().unpack();
-    let hash_type = script.hash_type();
-    let args = script.args().raw_data();
-
-    let code_hash = h256_to_byte32(code_hash.into());
-    let args = Bytes::new_builder()
-        .set(args.into_iter().map(packed::Byte::new).collect())
-        .build();
-
-    let script = Script::new_builder()
-        .code_hash(code_hash)
-        .hash_type(hash_type)
-        .args(args)
-        .build();
-
-    Address::new_builder()
-        .set(AddressUnion::Script(script))
-        .build()
-}
-
 pub fn complete_co_build_message_with_actions(
     tx: TransactionView,
     actions: &[(Option<packed::Script>, SporeActionUnion)],
@@ -86,32 +54,47 @@ pub fn build_mint_spore_action(
     content: &[u8],
 ) -> SporeActionUnion {
     let to = internal::build_always_success_script(context, Default::default());
-    let mint = MintSpore::new_builder()
-        .spore_id(h256_to_byte32(nft_id))
-        .data_hash(h256_to_byte32(blake2b_256(content)))
-        .to(script_to_address(to))
-        .build();
-    SporeActionUnion::MintSpore(mint)
+    let mint = NativeMintSpore {
+        spore_id: nft_id,
+        to: to.into(),
+        data_hash: blake2b_256(content),
+    };
+    SporeActionUnion::MintSpore(mint.into())
+}
+
+pub fn build_mint_spore_action_with_proof(
+    context: &mut Context,
+    nft_id: [u8; 32],
+    content: &[u8],
+    allowlist_proof: Vec<[u8; 32]>,
+) -> SporeActionUnion {
+    let to = internal::build_always_success_script(context, Default::default());
+    let mint = NativeMintSporeWithProof {
+        spore_id: nft_id,
+        to: to.into(),
+        data_hash: blake2b_256(content),
+        allowlist_proof,
+    };
+    SporeActionUnion::MintSporeWithProof(mint.into())
 }
 
 pub fn build_transfer_spore_action(context: &mut Context, nft_id: [u8; 32]) -> SporeActionUnion {
     let script = internal::build_always_success_script(context, Default::default());
-    let address = script_to_address(script);
-    let transfer = TransferSpore::new_builder()
-        .spore_id(h256_to_byte32(nft_id))
-        .from(address.clone())
-        .to(address)
-        .build();
-    SporeActionUnion::TransferSpore(transfer)
+    let transfer = NativeTransferSpore {
+        spore_id: nft_id,
+        from: script.clone().into(),
+        to: script.into(),
+    };
+    SporeActionUnion::TransferSpore(transfer.into())
 }
 
 pub fn build_burn_spore_action(context: &mut Context, nft_id: [u8; 32]) -> SporeActionUnion {
     let from = internal::build_always_success_script(context, Default::default());
-    let burn = BurnSpore::new_builder()
-        .spore_id(h256_to_byte32(nft_id))
-        .from(script_to_address(from))
-        .build();
-    SporeActionUnion::BurnSpore(burn)
+    let burn = NativeBurnSpore {
+        spore_id: nft_id,
+        from: from.into(),
+    };
+    SporeActionUnion::BurnSpore(burn.into())
 }
 
 pub fn build_mint_cluster_action(
@@ -120,12 +103,12 @@ pub fn build_mint_cluster_action(
     content: &[u8],
 ) -> SporeActionUnion {
     let to = internal::build_always_success_script(context, Default::default());
-    let cluster_create = MintCluster::new_builder()
-        .cluster_id(h256_to_byte32(cluster_id))
-        .data_hash(h256_to_byte32(blake2b_256(content)))
-        .to(script_to_address(to))
-        .build();
-    SporeActionUnion::MintCluster(cluster_create)
+    let cluster_create = NativeMintCluster {
+        cluster_id,
+        to: to.into(),
+        data_hash: blake2b_256(content),
+    };
+    SporeActionUnion::MintCluster(cluster_create.into())
 }
 
 pub fn build_transfer_cluster_action(
@@ -133,13 +116,12 @@ pub fn build_transfer_cluster_action(
     cluster_id: [u8; 32],
 ) -> SporeActionUnion {
     let script = internal::build_always_success_script(context, Default::default());
-    let address = script_to_address(script);
-    let cluster_transfer = TransferCluster::new_builder()
-        .cluster_id(h256_to_byte32(cluster_id))
-        .from(address.clone())
-        .to(address)
-        .build();
-    SporeActionUnion::TransferCluster(cluster_transfer)
+    let cluster_transfer = NativeTransferCluster {
+        cluster_id,
+        from: script.clone().into(),
+        to: script.into(),
+    };
+    SporeActionUnion::TransferCluster(cluster_transfer.into())
 }
 
 pub fn build_mint_proxy_action(
@@ -148,12 +130,12 @@ pub fn build_mint_proxy_action(
     proxy_id: [u8; 32],
 ) -> SporeActionUnion {
     let to = internal::build_always_success_script(context, Default::default());
-    let proxy_create = MintProxy::new_builder()
-        .cluster_id(h256_to_byte32(cluster_id))
-        .proxy_id(h256_to_byte32(proxy_id))
-        .to(script_to_address(to))
-        .build();
-    SporeActionUnion::MintProxy(proxy_create)
+    let proxy_create = NativeMintProxy {
+        cluster_id,
+        proxy_id,
+        to: to.into(),
+    };
+    SporeActionUnion::MintProxy(proxy_create.into())
 }
 
 pub fn build_transfer_proxy_action(
@@ -162,15 +144,13 @@ pub fn build_transfer_proxy_action(
     proxy_id: [u8; 32],
 ) -> SporeActionUnion {
     let script = internal::build_always_success_script(context, Default::default());
-    let from = script_to_address(script);
-    let to = from.clone();
-    let proxy_transfer = TransferProxy::new_builder()
-        .cluster_id(h256_to_byte32(cluster_id))
-        .proxy_id(h256_to_byte32(proxy_id))
-        .from(from)
-        .to(to)
-        .build();
-    SporeActionUnion::TransferProxy(proxy_transfer)
+    let proxy_transfer = NativeTransferProxy {
+        cluster_id,
+        proxy_id,
+        from: script.clone().into(),
+        to: script.into(),
+    };
+    SporeActionUnion::TransferProxy(proxy_transfer.into())
 }
 
 pub fn build_burn_proxy_action(
@@ -178,14 +158,13 @@ pub fn build_burn_proxy_action(
     cluster_id: [u8; 32],
     proxy_id: [u8; 32],
 ) -> SporeActionUnion {
-    let script = internal::build_always_success_script(context, Default::default());
-    let from = script_to_address(script);
-    let proxy_burn = BurnProxy::new_builder()
-        .cluster_id(h256_to_byte32(cluster_id))
-        .proxy_id(h256_to_byte32(proxy_id))
-        .from(from)
-        .build();
-    SporeActionUnion::BurnProxy(proxy_burn)
+    let from = internal::build_always_success_script(context, Default::default());
+    let proxy_burn = NativeBurnProxy {
+        cluster_id,
+        proxy_id,
+        from: from.into(),
+    };
+    SporeActionUnion::BurnProxy(proxy_burn.into())
 }
 
 pub fn build_mint_agent_action(
@@ -194,12 +173,12 @@ pub fn build_mint_agent_action(
     proxy_id: [u8; 32],
 ) -> SporeActionUnion {
     let to = internal::build_always_success_script(context, Default::default());
-    let agent_create = MintAgent::new_builder()
-        .cluster_id(h256_to_byte32(cluster_id))
-        .proxy_id(h256_to_byte32(proxy_id))
-        .to(script_to_address(to))
-        .build();
-    SporeActionUnion::MintAgent(agent_create)
+    let agent_create = NativeMintAgent {
+        cluster_id,
+        proxy_id,
+        to: to.into(),
+    };
+    SporeActionUnion::MintAgent(agent_create.into())
 }
 
 pub fn build_transfer_agent_action(
@@ -207,22 +186,19 @@ pub fn build_transfer_agent_action(
     cluster_id: [u8; 32],
 ) -> SporeActionUnion {
     let script = internal::build_always_success_script(context, Default::default());
-    let from = script_to_address(script);
-    let to = from.clone();
-    let agent_transfer = TransferAgent::new_builder()
-        .cluster_id(h256_to_byte32(cluster_id))
-        .from(from)
-        .to(to)
-        .build();
-    SporeActionUnion::TransferAgent(agent_transfer)
+    let agent_transfer = NativeTransferAgent {
+        cluster_id,
+        from: script.clone().into(),
+        to: script.into(),
+    };
+    SporeActionUnion::TransferAgent(agent_transfer.into())
 }
 
 pub fn build_burn_agent_action(context: &mut Context, cluster_id: [u8; 32]) -> SporeActionUnion {
-    let script = internal::build_always_success_script(context, Default::default());
-    let from = script_to_address(script);
-    let agent_burn = BurnAgent::new_builder()
-        .cluster_id(h256_to_byte32(cluster_id))
-        .from(from)
-        .build();
-    SporeActionUnion::BurnAgent(agent_burn)
+    let from = internal::build_always_success_script(context, Default::default());
+    let agent_burn = NativeBurnAgent {
+        cluster_id,
+        from: from.into(),
+    };
+    SporeActionUnion::BurnAgent(agent_burn.into())
 }
@@ -10,8 +10,12 @@ use ckb_testtool::context::Context;
 use spore_types::generated::action::SporeActionUnion;
 use std::num::ParseIntError;
 
-use spore_types::generated::spore::{ClusterDataV2 as ClusterData, SporeData};
+use spore_types::generated::mutant_data::{self, MutantData};
+use spore_types::generated::mutant_rules::{self, MutantRules};
+use spore_types::generated::spore::{ClusterDataV2 as ClusterData, ClusterDataV3, SporeData};
+use spore_types::native::{NativeClusterDataV2, NativeClusterDataV3, NativePublicMint};
 use spore_types::NativeNFTData;
+use spore_utils::Mime;
 
 use crate::Loader;
 
@@ -22,11 +26,51 @@ pub const UNIFORM_CAPACITY: u64 = 1000u64;
 pub const CAPACITY_UNIT: u64 = 100_000_000;
 
 pub fn build_serialized_cluster_data(name: &str, description: &str) -> ClusterData {
-    ClusterData::new_builder()
-        .name(name.as_bytes().into())
-        .description(description.as_bytes().into())
-        .mutant_id(Default::default())
-        .build()
+    NativeClusterDataV2::new(name, description).into()
+}
+
+pub fn build_serialized_cluster_data_with_allowlist(
+    name: &str,
+    description: &str,
+    allowlist_root: [u8; 32],
+) -> ClusterDataV3 {
+    NativeClusterDataV3 {
+        allowlist_root: Some(allowlist_root.to_vec()),
+        ..NativeClusterDataV3::new(name, description)
+    }
+    .into()
+}
+
+pub fn build_serialized_cluster_data_with_public_mint(
+    name: &str,
+    description: &str,
+    start_epoch: u64,
+    price: u64,
+) -> ClusterDataV3 {
+    let public_mint = NativePublicMint { start_epoch, price };
+    NativeClusterDataV3 {
+        public_mint: Some(public_mint),
+        ..NativeClusterDataV3::new(name, description)
+    }
+    .into()
+}
+
+pub fn build_serialized_cluster_data_with_lifecycle_mutant(
+    name: &str,
+    description: &str,
+    lifecycle_mutant_id: [u8; 32],
+) -> ClusterDataV3 {
+    NativeClusterDataV3 {
+        lifecycle_mutant_id: Some(lifecycle_mutant_id.to_vec()),
+        ..NativeClusterDataV3::new(name, description)
+    }
+    .into()
+}
+
+pub fn build_default_lock_hash(context: &mut Context) -> [u8; 32] {
+    internal::build_always_success_script(context, Default::default())
+        .calc_script_hash()
+        .unpack()
 }
 
 pub fn build_serialized_spore_data(
@@ -72,6 +116,92 @@ pub fn build_spore_type_script_with_payment(
     context.build_script_with_hash_type(out_point, ScriptHashType::Data1, args.into())
 }
 
+pub fn build_updatable_mutant_type_script(
+    context: &mut Context,
+    out_point: &OutPoint,
+    mutant_id: &[u8; 32],
+) -> Option<Script> {
+    let args = vec![mutant_id.to_vec(), 0u64.to_le_bytes().to_vec(), vec![1u8]].concat();
+    build_spore_type_script(context, out_point, args.into())
+}
+
+pub fn build_updatable_mutant_data(version: u64, lua_code: &str) -> Vec<u8> {
+    vec![version.to_le_bytes().to_vec(), lua_code.as_bytes().to_vec()].concat()
+}
+
+pub fn build_serialized_mutant_rules(
+    allowed_operations: u8,
+    recipient_lock_code_hashes: Vec<[u8; 32]>,
+    holding_period: Option<u64>,
+) -> MutantRules {
+    let recipient_lock_code_hashes = recipient_lock_code_hashes
+        .into_iter()
+        .map(|code_hash| mutant_rules::Byte32::new_unchecked(code_hash.to_vec().into()))
+        .collect::<Vec<_>>();
+    let holding_period = holding_period
+        .map(|since| mutant_rules::Uint64::new_unchecked(since.to_le_bytes().to_vec().into()));
+    MutantRules::new_builder()
+        .allowed_operations(allowed_operations.into())
+        .recipient_lock_code_hashes(
+            mutant_rules::Byte32Vec::new_builder()
+                .set(recipient_lock_code_hashes)
+                .build(),
+        )
+        .holding_period(
+            mutant_rules::Uint64Opt::new_builder()
+                .set(holding_period)
+                .build(),
+        )
+        .build()
+}
+
+// content type of plain text applied with mutants
+pub fn build_mutant_content_type(mutant_ids: &[[u8; 32]]) -> String {
+    mutant_ids
+        .iter()
+        .fold(Mime::new("plain", "test"), |mime, mutant_id| {
+            mime.mutant(*mutant_id)
+        })
+        .to_string()
+}
+
+pub fn build_serialized_mutant_data(
+    name: &[u8],
+    abi: Option<&[u8]>,
+    errors: &[(u8, &[u8])],
+    lua_code: &str,
+) -> MutantData {
+    let to_bytes = |data: &[u8]| {
+        let bytes = vec![(data.len() as u32).to_le_bytes().to_vec(), data.to_vec()].concat();
+        mutant_data::Bytes::new_unchecked(bytes.into())
+    };
+    let errors = errors
+        .iter()
+        .map(|(code, message)| {
+            mutant_data::MutantErrorMessage::new_builder()
+                .code((*code).into())
+                .message(to_bytes(message))
+                .build()
+        })
+        .collect();
+    MutantData::new_builder()
+        .name(to_bytes(name))
+        .description(to_bytes("a mutant with metadata".as_bytes()))
+        .author(to_bytes("spore".as_bytes()))
+        .abi(
+            mutant_data::BytesOpt::new_builder()
+                .set(abi.map(to_bytes))
+                .build(),
+        )
+        .errors(
+            mutant_data::MutantErrorMessages::new_builder()
+                .set(errors)
+                .build(),
+        )
+        .code(to_bytes(lua_code.as_bytes()))
+        .build()
+}
+
 pub fn build_spore_input(
     context: &mut Context,
     spore_type: Option<Script>,
@@ -127,6 +257,21 @@ pub fn build_normal_input(context: &mut Context) -> CellInput {
     )
 }
 
+pub fn build_normal_input_with_since(context: &mut Context, since: u64) -> CellInput {
+    build_normal_input(context)
+        .as_builder()
+        .since(since.pack())
+        .build()
+}
+
+pub fn build_normal_output_with_lock_args(
+    context: &mut Context,
+    capacity: u64,
+    lock_args: &[u8],
+) -> CellOutput {
+    internal::build_output(context, capacity, None, lock_args.to_vec().into())
+}
+
 pub fn build_normal_output_cell_with_type(
     context: &mut Context,
     type_: Option<Script>,
@@ -228,12 +373,13 @@ pub fn build_spore_output_materials(
     (output_data, normal_input, spore_output, spore_script_dep)
 }
 
-pub fn build_single_spore_mint_tx_with_extra_action(
+fn build_single_spore_mint_tx_with_proof_and_extra_action(
     context: &mut Context,
     content: Vec<u8>,
     content_type: &str,
     input_data: Option<SporeData>,
     cluster_id: Option<[u8; 32]>,
+    allowlist_proof: Option<Vec<[u8; 32]>>,
     mut actions: Vec<(Option<Script>, SporeActionUnion)>,
 ) -> TransactionView {
     let output_data =
@@ -264,11 +410,56 @@ pub fn build_single_spore_mint_tx_with_extra_action(
         .cell_dep(spore_script_dep)
         .build();
 
-    let action = co_build::build_mint_spore_action(context, type_id, output_data.as_slice());
+    let action = match allowlist_proof {
+        Some(proof) => co_build::build_mint_spore_action_with_proof(
+            context,
+            type_id,
+            output_data.as_slice(),
+            proof,
+        ),
+        None => co_build::build_mint_spore_action(context, type_id, output_data.as_slice()),
+    };
     actions.push((spore_type, action));
     co_build::complete_co_build_message_with_actions(tx, &actions)
 }
 
+pub fn build_single_spore_mint_tx_with_extra_action(
+    context: &mut Context,
+    content: Vec<u8>,
+    content_type: &str,
+    input_data: Option<SporeData>,
+    cluster_id: Option<[u8; 32]>,
+    actions: Vec<(Option<Script>, SporeActionUnion)>,
+) -> TransactionView {
+    build_single_spore_mint_tx_with_proof_and_extra_action(
+        context,
+        content,
+        content_type,
+        input_data,
+        cluster_id,
+        None,
+        actions,
+    )
+}
+
+pub fn build_single_spore_mint_tx_with_allowlist_proof(
+    context: &mut Context,
+    content: Vec<u8>,
+    content_type: &str,
+    cluster_id: [u8; 32],
+    allowlist_proof: Vec<[u8; 32]>,
+) -> TransactionView {
+    build_single_spore_mint_tx_with_proof_and_extra_action(
+        context,
+        content,
+        content_type,
+        None,
+        Some(cluster_id),
+        Some(allowlist_proof),
+        vec![],
+    )
+}
+
 pub fn build_single_spore_mint_tx(
     context: &mut Context,
     content: Vec<u8>,
@@ -437,6 +628,15 @@ pub fn build_single_mutant_celldep_tx(
     context: &mut Context,
     lua_code: &str,
     out_index: usize,
+) -> (TransactionView, [u8; 32]) {
+    build_single_mutant_celldep_tx_with_args_suffix(context, lua_code, out_index, &[])
+}
+
+pub fn build_single_mutant_celldep_tx_with_args_suffix(
+    context: &mut Context,
+    lua_code: &str,
+    out_index: usize,
+    args_suffix: &[u8],
 ) -> (TransactionView, [u8; 32]) {
     let (_, lua_lib_dep) = build_spore_contract_materials(context, "libckblua.so");
     let (lua_extension_out_point, lua_extension_script_dep) =
@@ -444,8 +644,13 @@ pub fn build_single_mutant_celldep_tx(
 
     let input_cell = build_normal_input(context);
     let mutant_id = build_type_id(&input_cell, out_index);
-    let type_ =
-        build_spore_type_script(context, &lua_extension_out_point, mutant_id.to_vec().into());
+    let type_ = build_spore_type_script(
+        context,
+        &lua_extension_out_point,
+        vec![mutant_id.to_vec(), args_suffix.to_vec()]
+            .concat()
+            .into(),
+    );
 
     let mutant_celldep =
         build_normal_cell_dep_with_lock_args(context, lua_code.as_bytes(), type_, &mutant_id);
@@ -455,3 +660,52 @@ pub fn build_single_mutant_celldep_tx(
 
     (tx, mutant_id)
 }
+
+pub fn build_single_rules_mutant_celldep_tx(
+    context: &mut Context,
+    rules: &MutantRules,
+    out_index: usize,
+) -> (TransactionView, [u8; 32]) {
+    let (rules_extension_out_point, rules_extension_script_dep) =
+        build_spore_contract_materials(context, "spore_extension_rules");
+
+    let input_cell = build_normal_input(context);
+    let mutant_id = build_type_id(&input_cell, out_index);
+    let type_ = build_spore_type_script(
+        context,
+        &rules_extension_out_point,
+        mutant_id.to_vec().into(),
+    );
+
+    let mutant_celldep =
+        build_normal_cell_dep_with_lock_args(context, rules.as_slice(), type_, &mutant_id);
+    let tx = TransactionBuilder::default()
+        .cell_deps(vec![mutant_celldep, rules_extension_script_dep])
+        .build();
+
+    (tx, mutant_id)
+}
+
+pub fn build_single_updatable_mutant_celldep_tx(
+    context: &mut Context,
+    lua_code: &str,
+    version: u64,
+    out_index: usize,
+) -> (TransactionView, [u8; 32]) {
+    let (_, lua_lib_dep) = build_spore_contract_materials(context, "libckblua.so");
+    let (lua_extension_out_point, lua_extension_script_dep) =
+        build_spore_contract_materials(context, "spore_extension_lua");
+
+    let input_cell = build_normal_input(context);
+    let mutant_id = build_type_id(&input_cell, out_index);
+    let type_ = build_updatable_mutant_type_script(context, &lua_extension_out_point, &mutant_id);
+
+    let mutant_data = build_updatable_mutant_data(version, lua_code);
+    let mutant_celldep =
+        build_normal_cell_dep_with_lock_args(context, &mutant_data, type_, &mutant_id);
+    let tx = TransactionBuilder::default()
+        .cell_deps(vec![mutant_celldep, lua_extension_script_dep, lua_lib_dep])
+        .build();
+
+    (tx, mutant_id)
+}